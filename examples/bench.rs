@@ -1,3 +1,4 @@
+use std::hint::black_box;
 use std::time::Instant;
 use an_ok_avl_tree::AVLTree;
 
@@ -9,4 +10,75 @@ fn main() {
     }
     let elapsed_time = now.elapsed();
     println!("AVL Tree insert 10000 times took {} ms.", elapsed_time.as_millis());
-}
\ No newline at end of file
+
+    let now = Instant::now();
+    for _ in 0..1000000 {
+        black_box(tree.iter().next());
+    }
+    let elapsed_time = now.elapsed();
+    println!(
+        "AVL Tree iter().next() 1000000 times took {} ms.",
+        elapsed_time.as_millis()
+    );
+
+    let now = Instant::now();
+    for _ in 0..1000000 {
+        black_box(tree.min_pair());
+    }
+    let elapsed_time = now.elapsed();
+    println!(
+        "AVL Tree min_pair 1000000 times took {} ms.",
+        elapsed_time.as_millis()
+    );
+
+    let mut deleted = tree.clone();
+    let now = Instant::now();
+    for i in 0..10000 {
+        if i % 10 != 0 {
+            deleted.delete(i);
+        }
+    }
+    let elapsed_time = now.elapsed();
+    println!(
+        "AVL Tree delete 9000 of 10000 keys took {} ms.",
+        elapsed_time.as_millis()
+    );
+
+    let now = Instant::now();
+    tree.retain(|key, _| key % 10 == 0);
+    let elapsed_time = now.elapsed();
+    println!(
+        "AVL Tree retain 1000 of 10000 keys took {} ms.",
+        elapsed_time.as_millis()
+    );
+    assert_eq!(tree, deleted);
+
+    // 打乱插入顺序，使节点在堆上分散
+    let mut scattered = AVLTree::new();
+    for i in 0..100000u64 {
+        scattered.insert(i * 7919 % 100000, i);
+    }
+    let now = Instant::now();
+    let mut sum = 0;
+    for _ in 0..100 {
+        sum += scattered.iter().map(|(_, value)| value).sum::<u64>();
+    }
+    let elapsed_time = now.elapsed();
+    println!(
+        "AVL Tree in-order scan of 100000 keys 100 times took {} ms.",
+        elapsed_time.as_millis()
+    );
+
+    let compact = scattered.compact();
+    let now = Instant::now();
+    let mut compact_sum = 0;
+    for _ in 0..100 {
+        compact_sum += compact.iter().map(|(_, value)| value).sum::<u64>();
+    }
+    let elapsed_time = now.elapsed();
+    println!(
+        "CompactTree in-order scan of 100000 keys 100 times took {} ms.",
+        elapsed_time.as_millis()
+    );
+    assert_eq!(black_box(sum), black_box(compact_sum));
+}
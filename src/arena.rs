@@ -0,0 +1,121 @@
+use crate::compare::Comparator;
+use crate::AVLTree;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt::{self, Debug};
+
+// 连续数组中的节点，孩子用数组下标表示
+struct CompactNode<K, V> {
+    key: K,
+    value: V,
+    left: Option<u32>,
+    right: Option<u32>,
+}
+
+/// 所有节点分配在同一个连续数组中的只读AVL树，由AVLTree::compact构造
+/// 节点按键的升序存放，顺序遍历是对数组的线性扫描，查找沿下标表示的孩子下降，适合读多写少的场景
+/// 需要修改时用into_tree转换回AVLTree
+/// # Example
+/// ```
+/// use an_ok_avl_tree::AVLTree;
+/// let tree: AVLTree<i32, char> = vec![(1, 'a'), (2, 'b'), (3, 'c')].into_iter().collect();
+/// let compact = tree.compact();
+/// assert_eq!(compact.get(&2), Some(&'b'));
+/// let mut tree = compact.into_tree();
+/// tree.insert(4, 'd');
+/// assert_eq!(tree.len(), 4);
+/// ```
+pub struct CompactTree<K, V> {
+    nodes: Vec<CompactNode<K, V>>,
+    root: Option<u32>,
+    cmp: Comparator<K>,
+    bounds: Option<(K, K)>,
+}
+
+impl<K, V> CompactTree<K, V> {
+    // 由按键升序排列的键值对构建，孩子的划分方式与Node::from_sorted相同，因此转换回AVLTree后形状不变
+    pub(crate) fn new(pairs: Vec<(K, V)>, cmp: Comparator<K>, bounds: Option<(K, K)>) -> Self {
+        let mut nodes: Vec<CompactNode<K, V>> = pairs
+            .into_iter()
+            .map(|(key, value)| CompactNode {
+                key,
+                value,
+                left: None,
+                right: None,
+            })
+            .collect();
+        let n = nodes.len();
+        let root = Self::link(&mut nodes, 0, n);
+        CompactTree {
+            nodes,
+            root,
+            cmp,
+            bounds,
+        }
+    }
+
+    // 以[lo, hi)的中点为根连接孩子下标，返回根的下标
+    fn link(nodes: &mut [CompactNode<K, V>], lo: usize, hi: usize) -> Option<u32> {
+        if lo == hi {
+            return None;
+        }
+        let mid = lo + (hi - lo) / 2;
+        nodes[mid].left = Self::link(nodes, lo, mid);
+        nodes[mid].right = Self::link(nodes, mid + 1, hi);
+        Some(mid as u32)
+    }
+
+    /// 返回键值对的个数
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// 判断是否为空
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// 按键的升序遍历所有键值对，即顺序扫描底层数组
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.nodes.iter().map(|node| (&node.key, &node.value))
+    }
+}
+
+impl<K: Ord, V> CompactTree<K, V> {
+    /// 根据键查找对应的值，找不到返回None
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut index = self.root;
+        while let Some(i) = index {
+            let node = &self.nodes[i as usize];
+            index = match self.cmp.compare(&node.key, key) {
+                Ordering::Less => node.right,
+                Ordering::Greater => node.left,
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    /// 判断键是否存在
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+impl<K: Ord + Clone, V> CompactTree<K, V> {
+    /// 转换回可修改的AVLTree，保留比较规则和键的区间，时间复杂度O(n)
+    pub fn into_tree(self) -> AVLTree<K, V> {
+        let pairs = self
+            .nodes
+            .into_iter()
+            .map(|node| (node.key, node.value))
+            .collect();
+        AVLTree::from_sorted_parts(pairs, self.cmp, self.bounds)
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for CompactTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
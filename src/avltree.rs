@@ -1,12 +1,70 @@
-use crate::iterator::{RangePairIter, TraverseIter};
-use crate::node::{Node, Link};
-use std::collections::{Bound, VecDeque};
+use crate::arena::CompactTree;
+use crate::compare::Comparator;
+use crate::entry::{Entry, OccupiedEntry, OccupiedError, TryInsertError, VacantEntry};
+use crate::error::AvlError;
+use crate::iterator::{
+    IntoIter, IntoKeys, IntoValues, Iter, Keys, MergeIter, RangeMut, RangePairIter, RevIter,
+    RevRangeIter, SetOpKeys, SetOperation, TraverseIter, TraverseOrder, Values, ValuesMut,
+};
+use crate::node::{Link, Node, RotationStep};
+use crate::snapshot::{CachedRoot, SortedPairs};
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BinaryHeap, VecDeque};
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cmp::{Ordering, Reverse};
+use core::fmt::{self, Debug};
+use core::hash::{Hash, Hasher};
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::{Add, Bound, Index, RangeBounds, Sub};
 
+/// 按键排序的AVL树，键必须实现全序的Ord，
+/// f64等只实现了PartialOrd的类型中存在无法比较的值(如NaN)，会破坏树的结构，因此不能作为键
+/// # Example
+/// ```compile_fail
+/// use an_ok_avl_tree::AVLTree;
+/// let mut tree = AVLTree::new();
+/// tree.insert(f64::NAN, 'a');
+/// ```
 pub struct AVLTree<K, V> {
-    root: Link<K, V>,
+    // 根节点及as_slice_pairs缓存的扁平快照，对根节点的可变访问会清除快照
+    root: CachedRoot<K, V>,
+    // 键的比较规则，new构造的树使用键自身的Ord，new_by构造的树使用自定义的比较函数
+    cmp: Comparator<K>,
+    // 允许插入的键的闭区间，with_key_bounds构造的树只接受区间内的键，None表示不限制
+    bounds: Option<(K, K)>,
+}
+
+impl<K, V> AVLTree<K, V> {
+    // 由根节点构造AVL树
+    fn from_root(root: Link<K, V>) -> Self {
+        AVLTree {
+            root: CachedRoot::new(root),
+            cmp: Comparator::Natural,
+            bounds: None,
+        }
+    }
+
+    // 由根节点构造与当前树使用相同比较规则和键区间的AVL树
+    fn with_root<W>(&self, root: Link<K, W>) -> AVLTree<K, W>
+    where
+        K: Clone,
+    {
+        let mut tree = AVLTree::from_root(root);
+        tree.cmp = self.cmp.clone();
+        tree.bounds = self.bounds.clone();
+        tree
+    }
+
+    // 替换根节点，经过CachedRoot的DerefMut清除缓存
+    fn set_root(&mut self, root: Link<K, V>) {
+        *self.root = root;
+    }
 }
 
-impl<K: PartialOrd + Clone, V> AVLTree<K, V> {
+impl<K: Ord + Clone, V> AVLTree<K, V> {
     /// 构建一棵空的AVL树
     /// # Examples
     /// ```
@@ -14,7 +72,96 @@ impl<K: PartialOrd + Clone, V> AVLTree<K, V> {
     /// let mut tree: AVLTree<i32, i32> = AVLTree::new();
     /// ```
     pub fn new() -> Self {
-        Self { root: None }
+        Self::from_root(None)
+    }
+
+    /// 构建一棵按自定义比较函数排序的空AVL树，插入、删除、查找和范围边界都按cmp比较键
+    /// 比较函数必须是全序的，否则树的结构会被破坏；append、merge_iter等组合两棵树的操作要求两棵树的比较规则相同
+    /// # Examples
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new_by(|a: &i32, b: &i32| b.cmp(a));
+    /// tree.insert(1, 'a');
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.keys().collect::<Vec<_>>(), vec![&3, &2, &1]);
+    /// assert_eq!(tree.min_pair(), Some((&3, &'c')));
+    /// ```
+    pub fn new_by<F>(cmp: F) -> Self
+    where
+        F: Fn(&K, &K) -> Ordering + Send + Sync + 'static,
+    {
+        let mut tree = Self::new();
+        tree.cmp = Comparator::Custom(Arc::new(cmp));
+        tree
+    }
+
+    /// 构建一棵只接受闭区间[min, max]内的键的空AVL树，适合每个分片负责一段键区间的场景
+    /// insert、insert_traced和extend忽略区间外的键，checked_insert、replace_key、entry、try_insert和get_or_insert_with将其退回
+    /// 由当前树拆分或克隆得到的树沿用同一区间，append和merge_with合并进来的键不做检查
+    /// # Examples
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut shard = AVLTree::with_key_bounds(100, 199);
+    /// shard.insert(150, 'a');
+    /// shard.insert(250, 'b');
+    /// assert_eq!(shard.checked_insert(99, 'c'), Err((99, 'c')));
+    /// assert_eq!(shard.keys().collect::<Vec<_>>(), vec![&150]);
+    /// ```
+    pub fn with_key_bounds(min: K, max: K) -> Self {
+        let mut tree = Self::new();
+        tree.bounds = Some((min, max));
+        tree
+    }
+
+    // 判断键是否在允许插入的区间内
+    fn in_key_bounds(&self, key: &K) -> bool {
+        self.bounds.as_ref().map_or(true, |(min, max)| {
+            self.cmp.compare(key, min) != Ordering::Less
+                && self.cmp.compare(key, max) != Ordering::Greater
+        })
+    }
+
+    /// 用堆对多个按键升序排列的数据源做多路归并，一次性构建平衡的AVL树
+    /// 键重复时(包括同一数据源内的重复)按数据源的顺序调用combine(key, 已合并的值, 新值)合并
+    /// 数据源未按键升序排列时panic
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree = AVLTree::from_sorted_sources(
+    ///     vec![vec![(1, 1), (3, 3)], vec![(2, 2), (3, 30)]],
+    ///     |_, a, b| a + b,
+    /// );
+    /// assert_eq!(tree.iter().collect::<Vec<_>>(), vec![(&1, &1), (&2, &2), (&3, &33)]);
+    /// ```
+    pub fn from_sorted_sources<I: IntoIterator<Item = (K, V)>>(
+        sources: Vec<I>,
+        mut combine: impl FnMut(&K, V, V) -> V,
+    ) -> AVLTree<K, V> {
+        let mut iters: Vec<I::IntoIter> = sources.into_iter().map(|s| s.into_iter()).collect();
+        let mut heap = BinaryHeap::new();
+        for (source, iter) in iters.iter_mut().enumerate() {
+            if let Some((key, value)) = iter.next() {
+                heap.push(MergeHead { key, value, source });
+            }
+        }
+        let mut merged: Vec<(K, V)> = Vec::new();
+        while let Some(MergeHead { key, value, source }) = heap.pop() {
+            if let Some((key, value)) = iters[source].next() {
+                heap.push(MergeHead { key, value, source });
+            }
+            if let Some((last_key, last_value)) = merged.pop() {
+                if last_key == key {
+                    let value = combine(&last_key, last_value, value);
+                    merged.push((last_key, value));
+                    continue;
+                }
+                assert!(last_key < key, "sources must be sorted by key");
+                merged.push((last_key, last_value));
+            }
+            merged.push((key, value));
+        }
+        Self::from_sorted(merged)
     }
 
     /// 向AVL树中插入键值对，如果键已经存在，则替换旧值为新值
@@ -28,91 +175,1949 @@ impl<K: PartialOrd + Clone, V> AVLTree<K, V> {
     /// assert_eq!(tree.get(&2), Some(&'b'));
     /// ```
     pub fn insert(&mut self, key: K, value: V) {
-        match self.root.take() {
-            None => self.root = Some(Box::new(Node::new(key, value))),
-            Some(node) => self.root = Some(node.insert(key, value)),
+        if self.in_key_bounds(&key) {
+            self.insert_unchecked(key, value);
+        }
+    }
+
+    // 不检查键的区间直接插入，用于append等不做区间检查的操作以及放回原本就在树中的键值对
+    pub(crate) fn insert_unchecked(&mut self, key: K, value: V) {
+        let root = match self.root.take() {
+            None => Box::new(Node::new(key, value)),
+            Some(node) => node.insert(key, value, &self.cmp),
+        };
+        self.set_root(Some(root));
+    }
+
+    /// 插入键值对，键在with_key_bounds指定的区间外时不插入，将键值对通过Err退回
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::with_key_bounds(0, 9);
+    /// assert_eq!(tree.checked_insert(5, 'a'), Ok(()));
+    /// assert_eq!(tree.checked_insert(10, 'b'), Err((10, 'b')));
+    /// ```
+    pub fn checked_insert(&mut self, key: K, value: V) -> Result<(), (K, V)> {
+        if !self.in_key_bounds(&key) {
+            return Err((key, value));
+        }
+        self.insert(key, value);
+        Ok(())
+    }
+
+    /// 只在键不存在时插入键值对，返回插入后值的可变借用
+    /// 键已存在时不覆盖原有的值，返回的错误中持有已存在的位置和被拒绝插入的键值对；
+    /// 键在with_key_bounds指定的区间外时不插入，键值对通过错误退回
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::{AVLTree, TryInsertError};
+    /// let mut tree = AVLTree::with_key_bounds(0, 9);
+    /// assert_eq!(tree.try_insert(1, 'a').unwrap(), &'a');
+    /// match tree.try_insert(1, 'b') {
+    ///     Err(TryInsertError::Occupied(err)) => {
+    ///         assert_eq!(err.entry.get(), &'a');
+    ///         assert_eq!(err.value, 'b');
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// assert!(matches!(tree.try_insert(10, 'c'), Err(TryInsertError::OutOfBounds(10, 'c'))));
+    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// ```
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<&mut V, TryInsertError<'_, K, V>> {
+        if !self.in_key_bounds(&key) {
+            return Err(TryInsertError::OutOfBounds(key, value));
+        }
+        if !self.contains(&key) {
+            return Ok(VacantEntry::new(key, self).insert(value));
+        }
+        let cmp = &self.cmp;
+        let (existing_key, existing_value) = self
+            .root
+            .as_mut()
+            .and_then(|node| node.search_pair_mut(&key, cmp))
+            .expect("existing key not found");
+        Err(TryInsertError::Occupied(OccupiedError {
+            entry: OccupiedEntry::new(existing_key, existing_value),
+            key,
+            value,
+        }))
+    }
+
+    /// 插入键值对，并按发生顺序返回插入后平衡调整所做的旋转
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::{AVLTree, RotationKind, RotationStep};
+    /// let mut tree = AVLTree::new();
+    /// assert!(tree.insert_traced(1, 'a').is_empty());
+    /// assert!(tree.insert_traced(2, 'b').is_empty());
+    /// assert_eq!(
+    ///     tree.insert_traced(3, 'c'),
+    ///     vec![RotationStep { pivot: 1, kind: RotationKind::RR }]
+    /// );
+    /// ```
+    pub fn insert_traced(&mut self, key: K, value: V) -> Vec<RotationStep<K>> {
+        let mut trace = Vec::new();
+        if !self.in_key_bounds(&key) {
+            return trace;
+        }
+        let root = match self.root.take() {
+            None => Box::new(Node::new(key, value)),
+            Some(node) => node.insert_traced(key, value, Some(&mut trace), &self.cmp),
+        };
+        self.set_root(Some(root));
+        trace
+    }
+
+    /// 返回键对应的位置，用于就地查询、插入或修改
+    /// 键在with_key_bounds指定的区间外时无法插入，通过Err退回键
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::{AVLTree, Entry};
+    /// let mut tree = AVLTree::with_key_bounds(0, 9);
+    /// tree.insert(1, 'a');
+    /// assert!(matches!(tree.entry(1), Ok(Entry::Occupied(_))));
+    /// assert!(matches!(tree.entry(2), Ok(Entry::Vacant(_))));
+    /// assert!(matches!(tree.entry(10), Err(10)));
+    /// ```
+    pub fn entry(&mut self, key: K) -> Result<Entry<'_, K, V>, K> {
+        if !self.in_key_bounds(&key) {
+            return Err(key);
+        }
+        if !self.contains(&key) {
+            return Ok(Entry::Vacant(VacantEntry::new(key, self)));
+        }
+        let cmp = &self.cmp;
+        let (key, value) = self
+            .root
+            .as_mut()
+            .and_then(|node| node.search_pair_mut(&key, cmp))
+            .expect("existing key not found");
+        Ok(Entry::Occupied(OccupiedEntry::new(key, value)))
+    }
+
+    /// 返回键对应的值的可变借用，键不存在时先插入f()的结果，f只在键不存在时调用
+    /// 键在with_key_bounds指定的区间外时不调用f，通过Err退回键
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::with_key_bounds(0, 9);
+    /// *tree.get_or_insert_with(1, || 10).unwrap() += 1;
+    /// *tree.get_or_insert_with(1, || 10).unwrap() += 1;
+    /// assert_eq!(tree.get(&1), Some(&12));
+    /// assert_eq!(tree.get_or_insert_with(10, || 10), Err(10));
+    /// ```
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, f: F) -> Result<&mut V, K> {
+        self.entry(key).map(|entry| entry.or_insert_with(f))
+    }
+
+    /// 从AVL树中删除键值对，如果找不到键值对，则忽略
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.delete(1);
+    /// assert!(tree.is_empty());
+    /// tree.delete(2);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn delete(&mut self, key: K) {
+        if let Some(node) = self.root.take() {
+            self.set_root(node.delete(key, &self.cmp));
+        }
+    }
+
+    /// 将old对应的值移动到新键new下：先删除old，new不存在时以new重新插入该值并返回Ok
+    /// new已经存在或在with_key_bounds指定的区间外时不插入，old仍被删除，其值通过Err交还给调用者；
+    /// old不存在时不做任何修改，返回Ok
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(5, 'e');
+    /// assert_eq!(tree.replace_key(&1, 2), Ok(()));
+    /// assert_eq!(tree.get(&2), Some(&'a'));
+    /// assert_eq!(tree.replace_key(&2, 5), Err('a'));
+    /// assert_eq!(tree.get(&5), Some(&'e'));
+    /// let mut shard = AVLTree::with_key_bounds(0, 9);
+    /// shard.insert(1, 'a');
+    /// assert_eq!(shard.replace_key(&1, 50), Err('a'));
+    /// assert!(shard.is_empty());
+    /// ```
+    pub fn replace_key(&mut self, old: &K, new: K) -> Result<(), V> {
+        if !self.contains(old) {
+            return Ok(());
+        }
+        // 在删除old之前判断new能否插入，old与new相同时new一定可以插入
+        let rejected = !self.in_key_bounds(&new)
+            || (self.cmp.compare(old, &new) != Ordering::Equal && self.contains(&new));
+        // 在old处拆分，old为右侧树中最小的键，取出后再连接两棵树
+        let mut upper = self.split_off(old);
+        let (_, value) = upper.pop_min().expect("old key exists");
+        self.append(&mut upper);
+        if rejected {
+            return Err(value);
+        }
+        self.insert_unchecked(new, value);
+        Ok(())
+    }
+
+    /// 按key拆分AVL树：键小于key的键值对留在当前树中，键大于等于key的键值对组成新树返回
+    /// 沿查找路径拆分并逐层连接，两棵树都保持平衡，时间复杂度O(log n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree: AVLTree<_, _> = (0..10).map(|key| (key, key * 10)).collect();
+    /// let upper = tree.split_off(&6);
+    /// assert_eq!(tree.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5]);
+    /// assert_eq!(upper.keys().copied().collect::<Vec<_>>(), vec![6, 7, 8, 9]);
+    /// assert!(tree.is_avl_tree() && upper.is_avl_tree());
+    /// ```
+    pub fn split_off(&mut self, key: &K) -> AVLTree<K, V> {
+        let (less, greater) = Node::split(self.root.take(), key, &self.cmp);
+        self.set_root(less);
+        self.with_root(greater)
+    }
+
+    /// 将other中的所有键值对移动到当前树中，键相同时保留other中的值，完成后other为空
+    /// 两棵树的键区间不相交时直接以other的一个端点连接两棵树，时间复杂度O(log n)；否则逐个插入
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree: AVLTree<_, _> = vec![(1, 'a'), (2, 'b')].into_iter().collect();
+    /// let mut other: AVLTree<_, _> = vec![(2, 'x'), (3, 'c')].into_iter().collect();
+    /// tree.append(&mut other);
+    /// assert_eq!(tree.get(&2), Some(&'x'));
+    /// assert_eq!(tree.get(&3), Some(&'c'));
+    /// assert!(other.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut AVLTree<K, V>) {
+        let other_root = other.root.take();
+        let mut other = other.with_root(other_root);
+        let (min, max) = match (self.min_pair(), self.max_pair()) {
+            (Some((min, _)), Some((max, _))) => (min, max),
+            _ => {
+                self.set_root(other.root.take());
+                return;
+            }
+        };
+        let (other_min, other_max) = match (other.min_pair(), other.max_pair()) {
+            (Some((min, _)), Some((max, _))) => (min, max),
+            _ => return,
+        };
+        if self.cmp.compare(max, other_min) == Ordering::Less {
+            let (key, value) = other.pop_min().expect("other is not empty");
+            let root = Node::join(self.root.take(), key, value, other.root.take());
+            self.set_root(Some(root));
+        } else if self.cmp.compare(other_max, min) == Ordering::Less {
+            let (key, value) = other.pop_max().expect("other is not empty");
+            let root = Node::join(other.root.take(), key, value, self.root.take());
+            self.set_root(Some(root));
+        } else {
+            for (key, value) in other {
+                self.insert_unchecked(key, value);
+            }
+        }
+    }
+
+    /// 将other中的键值对合并到当前树中，两棵树都包含的键调用resolver(key, 当前树的值, other的值)决定合并后的值
+    /// 按键的升序归并两棵树的键值对，再重新构建平衡的树，时间复杂度O(n+m)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut a: AVLTree<&str, i32> = vec![("x", 1), ("y", 2)].into_iter().collect();
+    /// let b: AVLTree<&str, i32> = vec![("y", 10), ("z", 3)].into_iter().collect();
+    /// a.merge_with(b, |_, mine, theirs| mine + theirs);
+    /// assert_eq!(a.get(&"x"), Some(&1));
+    /// assert_eq!(a.get(&"y"), Some(&12));
+    /// assert_eq!(a.get(&"z"), Some(&3));
+    /// ```
+    pub fn merge_with<F: FnMut(&K, V, V) -> V>(&mut self, other: AVLTree<K, V>, mut resolver: F) {
+        let mut mine = IntoIter::new(self.root.take()).peekable();
+        let mut theirs = other.into_iter().peekable();
+        let mut merged = Vec::new();
+        loop {
+            let order = match (mine.peek(), theirs.peek()) {
+                (Some((l, _)), Some((r, _))) => self.cmp.compare(l, r),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            let pair = match order {
+                Ordering::Less => mine.next(),
+                Ordering::Greater => theirs.next(),
+                Ordering::Equal => {
+                    let (key, value) = mine.next().expect("mine is not empty");
+                    let (_, other_value) = theirs.next().expect("theirs is not empty");
+                    let value = resolver(&key, value, other_value);
+                    Some((key, value))
+                }
+            };
+            merged.extend(pair);
+        }
+        self.set_sorted(merged);
+    }
+
+    /// 消耗当前树，按键的升序对每个值调用f，返回键和结构都相同、值为f的结果的新树
+    /// 键的顺序不变，因此直接复制节点结构而无需重新插入和旋转，时间复杂度O(n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, char> = vec![(1, 'a'), (2, 'b')].into_iter().collect();
+    /// let codes = tree.map_values(|&c| c as u32);
+    /// assert_eq!(codes.get(&2), Some(&98));
+    /// ```
+    pub fn map_values<W, F: FnMut(&V) -> W>(mut self, mut f: F) -> AVLTree<K, W> {
+        let root = self.root.take();
+        self.with_root(root.map(|root| Box::new(root.map_values(&mut f))))
+    }
+
+    /// 删除键不在range内的所有键值对
+    /// 在两端边界处各拆分一次，直接丢弃范围外的部分，时间复杂度O(log n)(不计释放节点的开销)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree: AVLTree<_, _> = (0..10).map(|key| (key, ())).collect();
+    /// tree.retain_range(3..=5);
+    /// assert_eq!(tree.keys().copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+    /// ```
+    pub fn retain_range<R: RangeBounds<K>>(&mut self, range: R) {
+        let mut kept = match range.start_bound() {
+            Bound::Included(key) => self.split_off(key),
+            Bound::Excluded(key) => {
+                let mut upper = self.split_off(key);
+                if upper
+                    .min_pair()
+                    .is_some_and(|(min, _)| self.cmp.compare(min, key).is_eq())
+                {
+                    upper.pop_min();
+                }
+                upper
+            }
+            Bound::Unbounded => {
+                let root = self.root.take();
+                self.with_root(root)
+            }
+        };
+        match range.end_bound() {
+            Bound::Included(key) => {
+                let mut upper = kept.split_off(key);
+                if upper
+                    .min_pair()
+                    .is_some_and(|(min, _)| kept.cmp.compare(min, key).is_eq())
+                {
+                    let (key, value) = upper.pop_min().expect("upper is not empty");
+                    kept.insert_unchecked(key, value);
+                }
+            }
+            Bound::Excluded(key) => {
+                kept.split_off(key);
+            }
+            Bound::Unbounded => {}
+        }
+        *self = kept;
+    }
+
+    /// 删除键在range内的所有键值对，返回删除的个数
+    /// 与retain_range相同，在两端边界处各拆分一次，再将范围两侧的树连接起来，时间复杂度O(log n)(不计释放节点的开销)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree: AVLTree<_, _> = (0..10).map(|key| (key, ())).collect();
+    /// assert_eq!(tree.remove_range(3..=5), 3);
+    /// assert_eq!(tree.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2, 6, 7, 8, 9]);
+    /// ```
+    pub fn remove_range<R: RangeBounds<K>>(&mut self, range: R) -> usize {
+        let mut removed = match range.start_bound() {
+            Bound::Included(key) => self.split_off(key),
+            Bound::Excluded(key) => {
+                let mut upper = self.split_off(key);
+                if upper
+                    .min_pair()
+                    .is_some_and(|(min, _)| self.cmp.compare(min, key).is_eq())
+                {
+                    let (key, value) = upper.pop_min().expect("upper is not empty");
+                    self.insert_unchecked(key, value);
+                }
+                upper
+            }
+            Bound::Unbounded => {
+                let root = self.root.take();
+                self.with_root(root)
+            }
+        };
+        let mut upper = match range.end_bound() {
+            Bound::Included(key) => {
+                let mut upper = removed.split_off(key);
+                if upper
+                    .min_pair()
+                    .is_some_and(|(min, _)| removed.cmp.compare(min, key).is_eq())
+                {
+                    let (key, value) = upper.pop_min().expect("upper is not empty");
+                    removed.insert_unchecked(key, value);
+                }
+                upper
+            }
+            Bound::Excluded(key) => removed.split_off(key),
+            Bound::Unbounded => self.with_root(None),
+        };
+        self.append(&mut upper);
+        removed.len()
+    }
+
+    /// 从一端逐个淘汰键值对，直到估算的内存占用不超过budget
+    /// 每个键值对的占用估算为节点本身的大小加上sizer(value)；evict_largest_key为true时从最大键开始淘汰，否则从最小键开始
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// for i in 0..10 {
+    ///     tree.insert(i, i);
+    /// }
+    /// tree.prune_to_bytes(5500, |_| 1000, true);
+    /// assert_eq!(tree.keys().collect::<Vec<_>>(), vec![&0, &1, &2, &3, &4]);
+    /// ```
+    pub fn prune_to_bytes<F: Fn(&V) -> usize>(
+        &mut self,
+        budget: usize,
+        sizer: F,
+        evict_largest_key: bool,
+    ) {
+        let node_overhead = mem::size_of::<Node<K, V>>();
+        let mut total: usize = self
+            .values()
+            .map(|value| node_overhead + sizer(value))
+            .sum();
+        while total > budget {
+            let extreme = if evict_largest_key {
+                self.max_pair()
+            } else {
+                self.min_pair()
+            };
+            let (key, size) = match extreme {
+                Some((key, value)) => (key.clone(), node_overhead + sizer(value)),
+                None => break,
+            };
+            self.delete(key);
+            total -= size;
+        }
+    }
+
+    /// 对keys中每个存在于树中的键调用f修改其值，不存在的键被忽略，返回被修改的键的个数
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 10);
+    /// tree.insert(2, 20);
+    /// assert_eq!(tree.update_keys(vec![1, 3], |_, value| *value += 1), 1);
+    /// assert_eq!(tree.get(&1), Some(&11));
+    /// ```
+    pub fn update_keys<I: IntoIterator<Item = K>, F: FnMut(&K, &mut V)>(
+        &mut self,
+        keys: I,
+        mut f: F,
+    ) -> usize {
+        let cmp = &self.cmp;
+        let mut count = 0;
+        for key in keys {
+            if let Some((key, value)) = self
+                .root
+                .as_mut()
+                .and_then(|node| node.search_pair_mut(&key, cmp))
+            {
+                f(key, value);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// 从树中删除other中存在的所有键
+    /// 合并两棵树的有序键序列一次找出保留的键值对，再重新构建平衡的树，时间复杂度O(n + m)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut a: AVLTree<i32, char> = vec![(1, 'a'), (2, 'b'), (3, 'c')].into_iter().collect();
+    /// let b: AVLTree<i32, char> = vec![(2, 'x'), (4, 'y')].into_iter().collect();
+    /// a.difference_update(&b);
+    /// assert_eq!(a.keys().collect::<Vec<_>>(), vec![&1, &3]);
+    /// ```
+    pub fn difference_update(&mut self, other: &AVLTree<K, V>) {
+        self.retain_by_keys_of(other, false);
+    }
+
+    /// 只保留树中在other中也存在的键，值仍取自self
+    /// 合并两棵树的有序键序列一次找出保留的键值对，再重新构建平衡的树，时间复杂度O(n + m)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut a: AVLTree<i32, char> = vec![(1, 'a'), (2, 'b'), (3, 'c')].into_iter().collect();
+    /// let b: AVLTree<i32, char> = vec![(2, 'x'), (4, 'y')].into_iter().collect();
+    /// a.intersection_update(&b);
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), vec![(&2, &'b')]);
+    /// ```
+    pub fn intersection_update(&mut self, other: &AVLTree<K, V>) {
+        self.retain_by_keys_of(other, true);
+    }
+
+    /// 只保留f返回true的键值对，删除其余键值对
+    /// 按键的升序遍历一次筛选出保留的键值对，再重新构建平衡的树，而不是逐个删除并旋转
+    /// 无论保留多少键值对，时间复杂度都是O(n)；删除大部分键值对时比逐个删除的O(k log n)更快
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree: AVLTree<i32, char> = vec![(1, 'a'), (2, 'b'), (3, 'c')].into_iter().collect();
+    /// tree.retain(|&key, _| key != 2);
+    /// assert_eq!(tree.keys().collect::<Vec<_>>(), vec![&1, &3]);
+    /// ```
+    pub fn retain<F: FnMut(&K, &V) -> bool>(&mut self, mut f: F) {
+        let survivors: Vec<(K, V)> = IntoIter::new(self.root.take())
+            .filter(|(key, value)| f(key, value))
+            .collect();
+        self.set_sorted(survivors);
+    }
+
+    /// 删除值与按键升序的前一个键值对相等的键值对，每段值相等的连续键值对只保留第一个
+    /// 与retain相同，遍历一次后由剩余的键值对重新构建平衡的树，时间复杂度O(n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree: AVLTree<i32, char> =
+    ///     vec![(1, 'a'), (2, 'a'), (3, 'b'), (4, 'a'), (5, 'a')].into_iter().collect();
+    /// tree.collapse_equal_values();
+    /// assert_eq!(tree.keys().collect::<Vec<_>>(), vec![&1, &3, &4]);
+    /// ```
+    pub fn collapse_equal_values(&mut self)
+    where
+        V: PartialEq,
+    {
+        let mut survivors: Vec<(K, V)> = Vec::new();
+        for (key, value) in IntoIter::new(self.root.take()) {
+            if survivors.last().is_some_and(|(_, last)| *last == value) {
+                continue;
+            }
+            survivors.push((key, value));
         }
+        self.set_sorted(survivors);
+    }
+
+    /// 删除pred返回true的所有键值对，并按键的升序返回被删除的键值对
+    /// 与retain相同，遍历一次后由剩余的键值对重新构建平衡的树，时间复杂度O(n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree: AVLTree<i32, char> = vec![(1, 'a'), (2, 'b'), (3, 'c')].into_iter().collect();
+    /// let removed = tree.extract_if(|&key, _| key != 2);
+    /// assert_eq!(removed, vec![(1, 'a'), (3, 'c')]);
+    /// assert_eq!(tree.keys().collect::<Vec<_>>(), vec![&2]);
+    /// ```
+    pub fn extract_if<F: FnMut(&K, &V) -> bool>(&mut self, mut pred: F) -> Vec<(K, V)> {
+        let (removed, survivors): (Vec<_>, Vec<_>) =
+            IntoIter::new(self.root.take()).partition(|(key, value)| pred(key, value));
+        self.set_sorted(survivors);
+        removed
+    }
+
+    /// 返回AVL树中键值对的个数，直接读取根节点保存的子树大小，时间复杂度O(1)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// assert_eq!(tree.len(), 0);
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        Node::size(&self.root) as usize
+    }
+
+    /// 估算AVL树的节点占用的字节数，仅用于诊断
+    /// 每个节点按size_of::<Node<K, V>>()加上一个usize的分配器开销估算，as_slice_pairs缓存的快照按键值对的大小计入，
+    /// 不包括K和V自身在堆上分配的内容，结果只是近似值
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// let empty = tree.memory_usage();
+    /// tree.insert(1, 'a');
+    /// assert!(tree.memory_usage() > empty);
+    /// ```
+    pub fn memory_usage(&self) -> usize {
+        let per_node = mem::size_of::<Node<K, V>>() + mem::size_of::<usize>();
+        let snapshot = self.root.cached_len() * mem::size_of::<(K, V)>();
+        mem::size_of::<Self>() + self.len() * per_node + snapshot
+    }
+
+    /// 判断当前AVL树是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree: AVLTree<i32, i32> = AVLTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// 返回AVL树的高度，空树的高度为0
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// assert_eq!(tree.height(), 0);
+    /// for key in vec![4, 2, 6, 1, 3, 5, 7] {
+    ///     tree.insert(key, key);
+    /// }
+    /// assert_eq!(tree.height(), 3);
+    /// ```
+    pub fn height(&self) -> u32 {
+        Node::height(&self.root)
+    }
+
+    /// 判断两棵树的内部结构是否相同，即每个位置上的键和节点高度都相等，不比较值
+    /// 内容相同但插入顺序不同的树可能形状不同，主要用于测试和演示平衡过程
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let a: AVLTree<i32, ()> = vec![(2, ()), (1, ()), (3, ())].into_iter().collect();
+    /// let b: AVLTree<i32, char> = vec![(1, 'a'), (2, 'b'), (3, 'c')].into_iter().collect();
+    /// assert!(a.same_shape(&b));
+    /// ```
+    pub fn same_shape<W>(&self, other: &AVLTree<K, W>) -> bool {
+        Node::same_shape(&self.root, &other.root)
+    }
+
+    /// 按键的升序取出所有键值对重新构建完全平衡的树，使树高降到最低的⌈log2(n+1)⌉，时间复杂度O(n)
+    /// 大量删除之后树可能比同样大小的完全平衡树更高，可以作为定期维护调用
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree: AVLTree<_, _> = (0..100).map(|key| (key, key)).collect();
+    /// for key in (0..100).filter(|key| key % 3 != 0) {
+    ///     tree.delete(key);
+    /// }
+    /// tree.rebalance();
+    /// assert_eq!(tree.height(), 6);
+    /// assert_eq!(tree.keys().count(), 34);
+    /// ```
+    pub fn rebalance(&mut self) {
+        let pairs: Vec<(K, V)> = IntoIter::new(self.root.take()).collect();
+        self.set_sorted(pairs);
+    }
+
+    /// 将树转换为所有节点分配在同一个连续数组中的只读CompactTree，保留比较规则和键的区间，时间复杂度O(n)
+    /// 每个节点单独分配时遍历需要在堆上跳转，连续存放能改善读多写少场景下的缓存命中，需要修改时再用into_tree转换回来
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, i32> = (0..100).map(|key| (key, key * 2)).collect();
+    /// let compact = tree.compact();
+    /// assert_eq!(compact.get(&21), Some(&42));
+    /// assert_eq!(compact.iter().count(), 100);
+    /// let tree = compact.into_tree();
+    /// assert_eq!(tree.get(&99), Some(&198));
+    /// ```
+    pub fn compact(mut self) -> CompactTree<K, V> {
+        let pairs: Vec<(K, V)> = IntoIter::new(self.root.take()).collect();
+        CompactTree::new(pairs, self.cmp.clone(), self.bounds.take())
+    }
+
+    // 由按比较规则cmp升序排列的键值对构建AVL树，用于CompactTree转换回来
+    pub(crate) fn from_sorted_parts(
+        pairs: Vec<(K, V)>,
+        cmp: Comparator<K>,
+        bounds: Option<(K, K)>,
+    ) -> Self {
+        let mut tree = Self::from_root(None);
+        tree.cmp = cmp;
+        tree.bounds = bounds;
+        tree.set_sorted(pairs);
+        tree
+    }
+
+    /// 根据键获取相应键值对
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.get_pair(&1), Some((&1, &'a')));
+    /// ```
+    pub fn get_pair(&self, key: &K) -> Option<(&K, &V)> {
+        self.root
+            .as_ref()
+            .and_then(|node| node.search_pair(key, &self.cmp))
+    }
+
+    /// 按输入顺序批量查找键值对，结果与逐个调用get_pair相同
+    /// 输入按键升序排列时复用上一次的查找路径，只回退到可能包含下一个键的子树再向下查找，相邻的键无需从根节点重新下降
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, char> = vec![(1, 'a'), (2, 'b'), (4, 'd')].into_iter().collect();
+    /// assert_eq!(
+    ///     tree.get_pairs(&[1, 3, 4]),
+    ///     vec![Some((&1, &'a')), None, Some((&4, &'d'))]
+    /// );
+    /// ```
+    pub fn get_pairs<'a>(&'a self, keys: &[K]) -> Vec<Option<(&'a K, &'a V)>> {
+        // 上一次的查找路径，每个节点附带其子树中键的上界(不含)，越往下上界越小
+        let mut path: Vec<(&'a Node<K, V>, Option<&'a K>)> = Vec::new();
+        let mut result = Vec::with_capacity(keys.len());
+        for (i, key) in keys.iter().enumerate() {
+            // 输入不是升序时从根节点重新查找
+            if i > 0 && self.cmp.compare(key, &keys[i - 1]) == Ordering::Less {
+                path.clear();
+            }
+            while let Some(&(_, Some(upper))) = path.last() {
+                if self.cmp.compare(key, upper) == Ordering::Less {
+                    break;
+                }
+                path.pop();
+            }
+            if path.is_empty() {
+                path.extend(self.root.as_deref().map(|root| (root, None)));
+            }
+            let mut found = None;
+            while let Some(&(node, upper)) = path.last() {
+                let (node_key, value) = node.pair();
+                let next = match self.cmp.compare(node_key, key) {
+                    Ordering::Equal => {
+                        found = Some((node_key, value));
+                        break;
+                    }
+                    Ordering::Less => node.right().as_deref().map(|right| (right, upper)),
+                    Ordering::Greater => node.left().as_deref().map(|left| (left, Some(node_key))),
+                };
+                match next {
+                    Some(next) => path.push(next),
+                    None => break,
+                }
+            }
+            result.push(found);
+        }
+        result
+    }
+
+    /// 根据键查找对应的值，找不到返回None，返回值的不可变借用
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.root
+            .as_ref()
+            .and_then(|node| node.search(key, &self.cmp))
+    }
+
+    /// 根据键查找对应的值，找不到返回None，返回值的可变借用
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// if let Some(value) = tree.get_mut(&1) {
+    ///     *value = 'b';
+    /// }
+    /// assert_eq!(tree.get(&1), Some(&'b'));
+    /// ```
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let cmp = &self.cmp;
+        self.root
+            .as_mut()
+            .and_then(|node| node.search_pair_mut(key, cmp))
+            .map(|(_, value)| value)
+    }
+
+    /// 根据键获取树中存储的键和值的可变借用，找不到返回None
+    /// 键只能读取，修改键可能破坏树的有序性
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 10);
+    /// if let Some((key, value)) = tree.get_pair_mut(&1) {
+    ///     *value += key;
+    /// }
+    /// assert_eq!(tree.get(&1), Some(&11));
+    /// ```
+    pub fn get_pair_mut(&mut self, key: &K) -> Option<(&K, &mut V)> {
+        let cmp = &self.cmp;
+        self.root
+            .as_mut()
+            .and_then(|node| node.search_pair_mut(key, cmp))
+    }
+
+    /// 同时返回多个键对应的值的可变借用，任一键不存在或者键之间有重复时返回None
+    /// 先将键排序，再从根节点一次下降，在每个节点处把键分给互不重叠的左右子树，时间复杂度O(N log N + N log n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// if let Some([a, b]) = tree.get_many_mut([&1, &2]) {
+    ///     std::mem::swap(a, b);
+    /// }
+    /// assert_eq!(tree.get(&1), Some(&'b'));
+    /// assert!(tree.get_many_mut([&1, &1]).is_none());
+    /// assert!(tree.get_many_mut([&1, &3]).is_none());
+    /// ```
+    pub fn get_many_mut<const N: usize>(&mut self, keys: [&K; N]) -> Option<[&mut V; N]> {
+        let mut order: [usize; N] = core::array::from_fn(|i| i);
+        order.sort_unstable_by(|&a, &b| self.cmp.compare(keys[a], keys[b]));
+        // 排序后相同的键相邻
+        if order
+            .windows(2)
+            .any(|pair| self.cmp.compare(keys[pair[0]], keys[pair[1]]) == Ordering::Equal)
+        {
+            return None;
+        }
+        let mut values: [Option<&mut V>; N] = core::array::from_fn(|_| None);
+        Node::search_many_mut(&mut self.root, &keys, &order, &mut values, &self.cmp);
+        if values.iter().any(Option::is_none) {
+            return None;
+        }
+        Some(values.map(|value| value.expect("every key was found")))
+    }
+
+    /// 交换键a和键b对应的值，任一键不存在或两个键相同时不修改树并返回false
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// assert!(tree.swap_values(&1, &2));
+    /// assert_eq!(tree.get(&1), Some(&'b'));
+    /// assert!(!tree.swap_values(&1, &3));
+    /// ```
+    pub fn swap_values(&mut self, a: &K, b: &K) -> bool {
+        match self.get_many_mut([a, b]) {
+            Some([a, b]) => {
+                mem::swap(a, b);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 返回键所在节点的深度，即从根节点到该节点的边数，根节点的深度为0，找不到返回None
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.depth(&2), Some(0));
+    /// assert_eq!(tree.depth(&1), Some(1));
+    /// assert_eq!(tree.depth(&3), None);
+    /// ```
+    pub fn depth(&self, key: &K) -> Option<usize> {
+        self.root
+            .as_ref()
+            .and_then(|node| node.depth(key, &self.cmp))
+    }
+
+    /// 据键查找对应的值，找不到返回默认值
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.get_or(&1, &'z'), &'a');
+    /// assert_eq!(tree.get_or(&2, &'z'), &'z');
+    /// ```
+    pub fn get_or<'a>(&'a self, key: &K, default: &'a V) -> &'a V {
+        self.get(key).map_or(default, |data| data)
+    }
+
+    /// 据键查找对应的值并返回其克隆，找不到返回V::default()，适合计数等累加的场景
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 10);
+    /// assert_eq!(tree.get_or_default(&1), 10);
+    /// assert_eq!(tree.get_or_default(&2), 0);
+    /// ```
+    pub fn get_or_default(&self, key: &K) -> V
+    where
+        V: Default + Clone,
+    {
+        self.get(key).cloned().unwrap_or_default()
+    }
+
+    /// 查找是否存在键值对
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.contains(&1), true);
+    /// assert_eq!(tree.contains(&2), false);
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// 查找是否存在键值对，与BTreeMap的同名方法一致，等价于contains
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.contains_key(&1), true);
+    /// assert_eq!(tree.contains_key(&2), false);
+    /// ```
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.contains(key)
+    }
+
+    /// 返回AVL树中的最小键值对，最小节点的位置缓存在树中，时间复杂度O(1)
+    /// 修改树之后缓存被清除，修改后的第一次调用沿左侧路径下降，时间复杂度O(log n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.min_pair(), Some((&1, &'a')));
+    /// ```
+    pub fn min_pair(&self) -> Option<(&K, &V)> {
+        self.root.min_node().map(Node::pair)
+    }
+
+    /// 返回AVL树中的最大键值对，与min_pair相同，最大节点的位置缓存在树中，时间复杂度O(1)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.max_pair(), Some((&3, &'c')));
+    /// ```
+    pub fn max_pair(&self) -> Option<(&K, &V)> {
+        self.root.max_node().map(Node::pair)
+    }
+
+    /// 返回AVL树中的第一个(键最小的)键值对，与BTreeMap的同名方法一致，等价于min_pair
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// assert_eq!(tree.first_key_value(), None);
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.first_key_value(), Some((&1, &'a')));
+    /// ```
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.min_pair()
+    }
+
+    /// 返回AVL树中的最后一个(键最大的)键值对，与BTreeMap的同名方法一致，等价于max_pair
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// assert_eq!(tree.last_key_value(), None);
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.last_key_value(), Some((&2, &'b')));
+    /// ```
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.max_pair()
+    }
+
+    /// 返回键最小的键值对，其中值为可变借用，键不变因此无需调整树
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(2, 20);
+    /// tree.insert(1, 10);
+    /// if let Some((_, value)) = tree.first_key_value_mut() {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(tree.min_pair(), Some((&1, &11)));
+    /// ```
+    pub fn first_key_value_mut(&mut self) -> Option<(&K, &mut V)> {
+        self.root.as_mut().map(|node| node.min_pair_mut())
+    }
+
+    /// 返回键最大的键值对，其中值为可变借用，键不变因此无需调整树
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(2, 20);
+    /// tree.insert(1, 10);
+    /// if let Some((_, value)) = tree.last_key_value_mut() {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(tree.max_pair(), Some((&2, &21)));
+    /// ```
+    pub fn last_key_value_mut(&mut self) -> Option<(&K, &mut V)> {
+        self.root.as_mut().map(|node| node.max_pair_mut())
+    }
+
+    /// 查看键最小的键值对而不移除，与min_pair相同，命名与优先队列的peek一致
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.peek_min(), Some((&1, &'a')));
+    /// ```
+    pub fn peek_min(&self) -> Option<(&K, &V)> {
+        self.min_pair()
+    }
+
+    /// 查看键最大的键值对而不移除，与max_pair相同，命名与优先队列的peek一致
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.peek_max(), Some((&2, &'b')));
+    /// ```
+    pub fn peek_max(&self) -> Option<(&K, &V)> {
+        self.max_pair()
+    }
+
+    /// 查看键最小的键值对而不移除，其中值为可变借用，与first_key_value_mut相同
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(2, 20);
+    /// tree.insert(1, 10);
+    /// if let Some((_, value)) = tree.peek_min_mut() {
+    ///     *value = 0;
+    /// }
+    /// assert_eq!(tree.peek_min(), Some((&1, &0)));
+    /// ```
+    pub fn peek_min_mut(&mut self) -> Option<(&K, &mut V)> {
+        self.first_key_value_mut()
+    }
+
+    /// 查看键最大的键值对而不移除，其中值为可变借用，与last_key_value_mut相同
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(2, 20);
+    /// tree.insert(1, 10);
+    /// if let Some((_, value)) = tree.peek_max_mut() {
+    ///     *value = 0;
+    /// }
+    /// assert_eq!(tree.peek_max(), Some((&2, &0)));
+    /// ```
+    pub fn peek_max_mut(&mut self) -> Option<(&K, &mut V)> {
+        self.last_key_value_mut()
+    }
+
+    /// 返回值最小的键值对，多个值同为最小时返回键最小的那个
+    /// 值没有索引，需要遍历所有键值对，时间复杂度O(n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, i32> = vec![(1, 30), (2, 10), (3, 20)].into_iter().collect();
+    /// assert_eq!(tree.min_by_value(), Some((&2, &10)));
+    /// ```
+    pub fn min_by_value(&self) -> Option<(&K, &V)>
+    where
+        V: Ord,
+    {
+        self.iter().min_by(|a, b| a.1.cmp(b.1))
+    }
+
+    /// 返回值最大的键值对，多个值同为最大时返回键最大的那个
+    /// 值没有索引，需要遍历所有键值对，时间复杂度O(n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, i32> = vec![(1, 30), (2, 10), (3, 20)].into_iter().collect();
+    /// assert_eq!(tree.max_by_value(), Some((&1, &30)));
+    /// ```
+    pub fn max_by_value(&self) -> Option<(&K, &V)>
+    where
+        V: Ord,
+    {
+        self.iter().max_by(|a, b| a.1.cmp(b.1))
+    }
+
+    /// 删除并返回AVL树中的最小键值对，树为空时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.pop_min(), Some((1, 'a')));
+    /// assert_eq!(tree.pop_min(), Some((2, 'b')));
+    /// assert_eq!(tree.pop_min(), None);
+    /// ```
+    pub fn pop_min(&mut self) -> Option<(K, V)> {
+        let (remain, min) = self.root.take()?.remove_min();
+        self.set_root(remain);
+        Some(min.into_pair())
+    }
+
+    /// 删除并返回AVL树中的最大键值对，树为空时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.pop_max(), Some((2, 'b')));
+    /// assert_eq!(tree.pop_max(), Some((1, 'a')));
+    /// assert_eq!(tree.pop_max(), None);
+    /// ```
+    pub fn pop_max(&mut self) -> Option<(K, V)> {
+        let (remain, max) = self.root.take()?.remove_max();
+        self.set_root(remain);
+        Some(max.into_pair())
+    }
+
+    /// 删除并返回第一个(键最小的)键值对，与BTreeMap的同名方法一致，等价于pop_min
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut map = AVLTree::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// while let Some((key, _val)) = map.pop_first() {
+    ///     assert!(map.iter().all(|(k, _v)| *k > key));
+    /// }
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        self.pop_min()
+    }
+
+    /// 删除并返回最后一个(键最大的)键值对，与BTreeMap的同名方法一致，等价于pop_max
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut map = AVLTree::new();
+    /// map.insert(1, "a");
+    /// map.insert(2, "b");
+    /// while let Some((key, _val)) = map.pop_last() {
+    ///     assert!(map.iter().all(|(k, _v)| *k < key));
+    /// }
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        self.pop_max()
+    }
+
+    /// 判断是否为AVL树，即中序遍历的键严格递增且每个节点都平衡，空树同样满足AVL树的性质
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// assert!(tree.is_avl_tree());
+    /// tree.insert(1, 'a');
+    /// assert!(tree.is_avl_tree());
+    /// ```
+    pub fn is_avl_tree(&self) -> bool {
+        // 逐个节点的检查只比较父子的键，隔代的顺序由is_bst保证
+        Node::is_avl_tree(&self.root, &self.cmp) && self.is_bst()
+    }
+
+    /// 返回键为key的节点的平衡因子，即左子树高度减右子树高度，AVL树中总在[-1, 1]之间；键不存在时返回None
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.balance_factor(&2), Some(1));
+    /// assert_eq!(tree.balance_factor(&1), Some(0));
+    /// assert_eq!(tree.balance_factor(&3), None);
+    /// ```
+    pub fn balance_factor(&self, key: &K) -> Option<i32> {
+        self.root
+            .as_ref()
+            .and_then(|node| node.balance_factor(key, &self.cmp))
+    }
+
+    /// 检查AVL树的所有性质，包括键的顺序、节点保存的高度和子树大小以及平衡因子，返回发现的第一处错误
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<_, _> = (0..100).map(|key| (key, ())).collect();
+    /// assert_eq!(tree.validate(), Ok(()));
+    /// ```
+    pub fn validate(&self) -> Result<(), AvlError<K>> {
+        Node::validate(&self.root, None, None, &self.cmp).map(|_| ())
+    }
+
+    /// 只检查二叉搜索树的有序性，即中序遍历的键严格递增，不检查树高和平衡
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// assert!(tree.is_bst());
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert!(tree.is_bst());
+    /// ```
+    pub fn is_bst(&self) -> bool {
+        let mut prev: Option<&K> = None;
+        for (key, _) in self.iter() {
+            if prev.is_some_and(|prev| !self.cmp.compare(prev, key).is_lt()) {
+                return false;
+            }
+            prev = Some(key);
+        }
+        true
+    }
+
+    /// 返回第n小(从0开始)的键值对，n超出范围返回None，时间复杂度O(log n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.select(0), Some((&1, &'a')));
+    /// assert_eq!(tree.select(2), Some((&3, &'c')));
+    /// assert_eq!(tree.select(3), None);
+    /// ```
+    pub fn select(&self, n: usize) -> Option<(&K, &V)> {
+        self.root.as_ref().and_then(|node| node.select(n))
+    }
+
+    /// 按键的升序输出排名(从0开始)在[start, end)内的键值对，适合分页
+    /// 按子树大小直接定位到排名为start的位置，不从头遍历，时间复杂度O(log n + (end - start))
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, ()> = (0..100).map(|key| (key * 2, ())).collect();
+    /// let page: Vec<&i32> = tree.select_range(10, 13).map(|(k, _)| k).collect();
+    /// assert_eq!(page, vec![&20, &22, &24]);
+    /// ```
+    pub fn select_range(&self, start: usize, end: usize) -> impl Iterator<Item = (&K, &V)> {
+        Iter::from_rank(&self.root, start).take(end.saturating_sub(start))
+    }
+
+    /// 返回树中严格小于key的键的个数，key可以不存在树中，是select的逆运算
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.rank(&1), 0);
+    /// assert_eq!(tree.rank(&3), 2);
+    /// assert_eq!(tree.rank(&10), 3);
+    /// ```
+    pub fn rank(&self, key: &K) -> usize {
+        self.root
+            .as_ref()
+            .map_or(0, |node| node.rank(key, &self.cmp))
+    }
+
+    /// 按键的升序用f依次合并范围内的值，范围为空时返回None
+    /// 节点没有缓存子树的聚合值，因此需要遍历范围内的键值对，时间复杂度O(log n + k)，k为范围内键值对的个数
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, i32> = (1..=6).map(|key| (key, key)).collect();
+    /// assert_eq!(tree.fold_range(2..=4, |acc, value| acc * value), Some(24));
+    /// assert_eq!(tree.fold_range(7.., |acc, value| acc * value), None);
+    /// ```
+    pub fn fold_range<R, F>(&self, range: R, mut f: F) -> Option<V>
+    where
+        R: RangeBounds<K>,
+        F: FnMut(V, &V) -> V,
+        V: Clone,
+    {
+        let mut iter = self.range(range);
+        let (_, first) = iter.next()?;
+        Some(iter.fold(first.clone(), |acc, (_, value)| f(acc, value)))
+    }
+
+    /// 返回范围内所有值的和，范围为空时返回None，时间复杂度O(log n + k)
+    /// 需要频繁对大范围求和时使用SumTree，它在每个节点中维护子树的和，时间复杂度为O(log n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, i32> = (1..=6).map(|key| (key, key * 10)).collect();
+    /// assert_eq!(tree.sum_range(2..=4), Some(90));
+    /// assert_eq!(tree.sum_range(..), Some(210));
+    /// assert_eq!(tree.sum_range(7..), None);
+    /// ```
+    pub fn sum_range<R: RangeBounds<K>>(&self, range: R) -> Option<V>
+    where
+        V: Add<Output = V> + Clone,
+    {
+        self.fold_range(range, |acc, value| acc + value.clone())
+    }
+
+    /// 返回范围内键的个数，由两端边界的rank相减得到，不遍历范围内的键值对，时间复杂度O(log n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, ()> = (1..=6).map(|key| (key, ())).collect();
+    /// assert_eq!(tree.count_range(2..=5), 4);
+    /// assert_eq!(tree.count_range(2..5), 3);
+    /// assert_eq!(tree.count_range(..), 6);
+    /// assert_eq!(tree.count_range(7..), 0);
+    /// ```
+    pub fn count_range<R: RangeBounds<K>>(&self, range: R) -> usize {
+        // 严格位于下边界之前的键的个数
+        let before = match range.start_bound() {
+            Bound::Included(key) => self.rank(key),
+            Bound::Excluded(key) => self.rank(key) + self.contains(key) as usize,
+            Bound::Unbounded => 0,
+        };
+        // 不超过上边界的键的个数
+        let until = match range.end_bound() {
+            Bound::Included(key) => self.rank(key) + self.contains(key) as usize,
+            Bound::Excluded(key) => self.rank(key),
+            Bound::Unbounded => Node::size(&self.root) as usize,
+        };
+        until.saturating_sub(before)
+    }
+
+    ///返回第一个大于key的键值对
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.successor(&1), Some((&2, &'b')));
+    /// assert_eq!(tree.successor(&0), Some((&1, &'a')));
+    /// assert_eq!(tree.successor(&3), None);
+    /// ```
+    pub fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        self.root
+            .as_ref()
+            .and_then(|node| node.successor(key, &self.cmp))
+    }
+
+    ///返回第一个小于key的键值对
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.predecessor(&3), Some((&2, &'b')));
+    /// assert_eq!(tree.predecessor(&5), Some((&3, &'c')));
+    /// assert_eq!(tree.predecessor(&1), None);
+    /// ```
+    pub fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        self.root
+            .as_ref()
+            .and_then(|node| node.predecessor(key, &self.cmp))
+    }
+
+    /// 返回第一个大于等于key的键值对：key存在时返回其本身，否则与successor相同，等价于ceiling
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(3, 'c');
+    /// assert_eq!(tree.successor_or_equal(&1), Some((&1, &'a')));
+    /// assert_eq!(tree.successor_or_equal(&2), Some((&3, &'c')));
+    /// ```
+    pub fn successor_or_equal(&self, key: &K) -> Option<(&K, &V)> {
+        self.ceiling(key)
+    }
+
+    /// 返回最后一个小于等于key的键值对：key存在时返回其本身，否则与predecessor相同，等价于floor
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(3, 'c');
+    /// assert_eq!(tree.predecessor_or_equal(&3), Some((&3, &'c')));
+    /// assert_eq!(tree.predecessor_or_equal(&2), Some((&1, &'a')));
+    /// ```
+    pub fn predecessor_or_equal(&self, key: &K) -> Option<(&K, &V)> {
+        self.floor(key)
+    }
+
+    /// 返回第一个大于key的键值对及其排名(比它小的键的个数)，排名在查找的同一次下降中得到，时间复杂度O(log n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.successor_indexed(&1), Some((1, &2, &'b')));
+    /// assert_eq!(tree.successor_indexed(&3), None);
+    /// ```
+    pub fn successor_indexed(&self, key: &K) -> Option<(usize, &K, &V)> {
+        Node::successor_indexed(&self.root, key, &self.cmp)
+    }
+
+    /// 返回第一个小于key的键值对及其排名(比它小的键的个数)，排名在查找的同一次下降中得到，时间复杂度O(log n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// assert_eq!(tree.predecessor_indexed(&3), Some((1, &2, &'b')));
+    /// assert_eq!(tree.predecessor_indexed(&1), None);
+    /// ```
+    pub fn predecessor_indexed(&self, key: &K) -> Option<(usize, &K, &V)> {
+        Node::predecessor_indexed(&self.root, key, &self.cmp)
+    }
+
+    /// 消耗AVL树，将每个键包装为Reverse后重新构建，新树按键的自然降序排列
+    /// 使用自定义比较函数的树需要逐个插入重建，否则直接按逆序批量构建
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// use std::cmp::Reverse;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// let reversed = tree.into_reversed();
+    /// assert_eq!(reversed.min_pair(), Some((&Reverse(2), &'b')));
+    /// ```
+    pub fn into_reversed(self) -> AVLTree<Reverse<K>, V> {
+        if !self.cmp.is_natural() {
+            return self
+                .into_iter()
+                .map(|(key, value)| (Reverse(key), value))
+                .collect();
+        }
+        let mut pairs: Vec<(Reverse<K>, V)> = self
+            .into_iter()
+            .map(|(key, value)| (Reverse(key), value))
+            .collect();
+        pairs.reverse();
+        AVLTree::from_sorted(pairs)
+    }
+
+    /// 范围迭代器
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// use std::collections::Bound;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// let res: Vec<(&i32, &char)> = tree.range_pair_iter(Bound::Unbounded, Bound::Unbounded).collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b'), (&3, &'c')]);
+    /// let res: Vec<(&i32, &char)> = tree.range_pair_iter(Bound::Included(0), Bound::Included(2)).collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b')]);
+    /// let res: Vec<(&i32, &char)> = tree.range_pair_iter(Bound::Excluded(1), Bound::Excluded(3)).collect();
+    /// assert_eq!(res, vec![(&2, &'b')]);
+    /// ```
+    pub fn range_pair_iter(&self, min: Bound<K>, max: Bound<K>) -> RangePairIter<'_, K, V> {
+        RangePairIter::new(&self.root, min, max, &self.cmp)
+    }
+
+    /// 按键的升序将values依次赋给范围内的键值对，范围或values任一耗尽即停止，键不变因此无需调整树
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// tree.insert(3, 'c');
+    /// tree.assign_range(2.., vec!['x', 'y', 'z']);
+    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// assert_eq!(tree.get(&2), Some(&'x'));
+    /// assert_eq!(tree.get(&3), Some(&'y'));
+    /// ```
+    pub fn assign_range<R: RangeBounds<K>, I: IntoIterator<Item = V>>(
+        &mut self,
+        range: R,
+        values: I,
+    ) {
+        let mut values = values.into_iter();
+        Node::range_for_each_mut(
+            &mut self.root,
+            &range,
+            &mut |_, value| {
+                if let Some(new_value) = values.next() {
+                    *value = new_value;
+                }
+            },
+            &self.cmp,
+        );
+    }
+
+    /// 按键的升序对范围内的每个键值对调用f，f可以原地修改值，键不变因此无需调整树
+    /// 直接递归访问节点，跳过范围外的子树，不需要像range_mut那样维护迭代器的栈
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree: AVLTree<i32, i32> = (1..=5).map(|key| (key, 0)).collect();
+    /// tree.apply_range(2..4, |key, value| *value += key);
+    /// assert_eq!(tree.get(&1), Some(&0));
+    /// assert_eq!(tree.get(&2), Some(&2));
+    /// assert_eq!(tree.get(&3), Some(&3));
+    /// assert_eq!(tree.get(&4), Some(&0));
+    /// ```
+    pub fn apply_range<R: RangeBounds<K>, F: FnMut(&K, &mut V)>(&mut self, range: R, mut f: F) {
+        Node::range_for_each_mut(&mut self.root, &range, &mut f, &self.cmp);
+    }
+
+    /// 按键的升序惰性遍历AVL树，不预先生成键列表
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// let res: Vec<(&i32, &char)> = tree.iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b'), (&3, &'c')]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(&self.root)
+    }
+
+    /// 从第一个大于等于key的键开始按键的升序惰性遍历，与range(key..)的结果相同，适合从某个键恢复扫描
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, ()> = (0..10).map(|key| (key * 2, ())).collect();
+    /// let keys: Vec<&i32> = tree.iter_from(&13).map(|(k, _)| k).collect();
+    /// assert_eq!(keys, vec![&14, &16, &18]);
+    /// ```
+    pub fn iter_from(&self, key: &K) -> Iter<'_, K, V> {
+        Iter::from_key(&self.root, key, &self.cmp)
+    }
+
+    /// 按键的降序惰性遍历AVL树，输出的顺序与iter完全相反，空间复杂度O(h)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
+    /// tree.insert(1, 'a');
+    /// let res: Vec<(&i32, &char)> = tree.iter_rev().collect();
+    /// assert_eq!(res, vec![(&3, &'c'), (&2, &'b'), (&1, &'a')]);
+    /// ```
+    pub fn iter_rev(&self) -> RevIter<'_, K, V> {
+        RevIter::new(&self.root)
+    }
+
+    /// 按升序返回所有键的迭代器
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.keys().collect::<Vec<_>>(), vec![&1, &2, &3]);
+    /// ```
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys::new(self.iter())
+    }
+
+    /// 按键的升序返回所有值的迭代器
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(1, 'a');
+    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.values().collect::<Vec<_>>(), vec![&'a', &'b', &'c']);
+    /// ```
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values::new(self.iter())
+    }
+
+    /// 消耗AVL树，按键的升序返回拥有所有权的键，与BTreeMap的同名方法一致
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, char> = vec![(2, 'b'), (1, 'a')].into_iter().collect();
+    /// assert_eq!(tree.into_keys().collect::<Vec<_>>(), vec![1, 2]);
+    /// ```
+    pub fn into_keys(mut self) -> IntoKeys<K, V> {
+        IntoKeys::new(IntoIter::new(self.root.take()))
+    }
+
+    /// 消耗AVL树，按键的升序返回拥有所有权的值，与BTreeMap的同名方法一致
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, char> = vec![(2, 'b'), (1, 'a')].into_iter().collect();
+    /// assert_eq!(tree.into_values().collect::<Vec<_>>(), vec!['a', 'b']);
+    /// ```
+    pub fn into_values(mut self) -> IntoValues<K, V> {
+        IntoValues::new(IntoIter::new(self.root.take()))
+    }
+
+    /// 消耗AVL树，转换为键按Ord排序的BTreeMap
+    /// 使用自定义比较规则的树转换后按键自身的Ord重新排序
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// use std::collections::BTreeMap;
+    /// let tree: AVLTree<i32, char> = vec![(2, 'b'), (1, 'a')].into_iter().collect();
+    /// let map: BTreeMap<i32, char> = tree.into_btreemap();
+    /// assert_eq!(map.into_iter().collect::<Vec<_>>(), vec![(1, 'a'), (2, 'b')]);
+    /// ```
+    pub fn into_btreemap(mut self) -> BTreeMap<K, V> {
+        IntoIter::new(self.root.take()).collect()
+    }
+
+    /// 按键的升序返回所有值的可变借用，可用于原地批量修改值
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 10);
+    /// tree.insert(2, 20);
+    /// for value in tree.values_mut() {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(tree.values().collect::<Vec<_>>(), vec![&11, &21]);
+    /// ```
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut::new(&mut self.root)
+    }
+
+    /// 按键的升序惰性合并两棵树的键值对，不构建新树；键相同时输出self中的键值对
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut a = AVLTree::new();
+    /// a.insert(1, 'a');
+    /// a.insert(2, 'b');
+    /// let mut b = AVLTree::new();
+    /// b.insert(2, 'x');
+    /// b.insert(3, 'c');
+    /// let res: Vec<(&i32, &char)> = a.merge_iter(&b).collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b'), (&3, &'c')]);
+    /// ```
+    pub fn merge_iter<'a>(&'a self, other: &'a AVLTree<K, V>) -> MergeIter<'a, K, V> {
+        MergeIter::new(self.iter(), other.iter(), &self.cmp)
+    }
+
+    /// 按树的顺序将键值对划分为连续的桶，start为桶中的第一个键，与start的距离小于width的键属于同一个桶
+    /// 下一个桶从距离不小于width的第一个键开始，因此不会输出空桶；width不为正时每个桶只包含一个键值对
+    /// 距离是按键自身的Ord计算的差值，与树的比较规则无关，因此new_by构造的降序树按降序划分桶；
+    /// 判断时计算差值与width比较，而不计算start + width，键靠近类型的上限时也不会溢出，有符号整数的键要求差值能用K表示
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, ()> = vec![(1, ()), (3, ()), (6, ()), (12, ())].into_iter().collect();
+    /// let buckets: Vec<Vec<i32>> = tree
+    ///     .chunk_by_key(5)
+    ///     .map(|bucket| bucket.into_iter().map(|(k, _)| *k).collect())
+    ///     .collect();
+    /// assert_eq!(buckets, vec![vec![1, 3], vec![6], vec![12]]);
+    /// ```
+    pub fn chunk_by_key(&self, width: K) -> impl Iterator<Item = Vec<(&K, &V)>> + '_
+    where
+        K: Sub<Output = K>,
+    {
+        let mut iter = self.iter().peekable();
+        core::iter::from_fn(move || {
+            let first = iter.next()?;
+            let start = first.0;
+            let mut bucket = Vec::from([first]);
+            while let Some(pair) = iter.next_if(|(key, _)| {
+                let offset = if *key >= start {
+                    (*key).clone() - start.clone()
+                } else {
+                    start.clone() - (*key).clone()
+                };
+                offset < width
+            }) {
+                bucket.push(pair);
+            }
+            Some(bucket)
+        })
+    }
+
+    /// 按键的升序惰性输出两棵树键的并集，时间复杂度O(n+m)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let a: AVLTree<i32, ()> = vec![1, 2, 3].into_iter().map(|k| (k, ())).collect();
+    /// let b: AVLTree<i32, ()> = vec![2, 3, 4].into_iter().map(|k| (k, ())).collect();
+    /// assert_eq!(a.union_keys(&b).copied().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    /// ```
+    pub fn union_keys<'a>(&'a self, other: &'a AVLTree<K, V>) -> impl Iterator<Item = &'a K> {
+        SetOpKeys::new(self.keys(), other.keys(), SetOperation::Union, &self.cmp)
+    }
+
+    /// 按键的升序惰性输出两棵树都包含的键，时间复杂度O(n+m)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let a: AVLTree<i32, ()> = vec![1, 2, 3].into_iter().map(|k| (k, ())).collect();
+    /// let b: AVLTree<i32, ()> = vec![2, 3, 4].into_iter().map(|k| (k, ())).collect();
+    /// assert_eq!(a.intersection_keys(&b).copied().collect::<Vec<_>>(), vec![2, 3]);
+    /// ```
+    pub fn intersection_keys<'a>(
+        &'a self,
+        other: &'a AVLTree<K, V>,
+    ) -> impl Iterator<Item = &'a K> {
+        SetOpKeys::new(
+            self.keys(),
+            other.keys(),
+            SetOperation::Intersection,
+            &self.cmp,
+        )
+    }
+
+    /// 按键的升序惰性输出在self中但不在other中的键，时间复杂度O(n+m)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let a: AVLTree<i32, ()> = vec![1, 2, 3].into_iter().map(|k| (k, ())).collect();
+    /// let b: AVLTree<i32, ()> = vec![2, 3, 4].into_iter().map(|k| (k, ())).collect();
+    /// assert_eq!(a.difference_keys(&b).copied().collect::<Vec<_>>(), vec![1]);
+    /// ```
+    pub fn difference_keys<'a>(&'a self, other: &'a AVLTree<K, V>) -> impl Iterator<Item = &'a K> {
+        SetOpKeys::new(
+            self.keys(),
+            other.keys(),
+            SetOperation::Difference,
+            &self.cmp,
+        )
+    }
+
+    /// 判断self的每个键是否都在other中，归并两棵树的有序键序列，发现第一个不在other中的键即返回，时间复杂度O(n+m)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let a: AVLTree<i32, ()> = vec![1, 2].into_iter().map(|k| (k, ())).collect();
+    /// let b: AVLTree<i32, ()> = vec![1, 2, 3].into_iter().map(|k| (k, ())).collect();
+    /// assert!(a.keys_subset_of(&b));
+    /// assert!(!b.keys_subset_of(&a));
+    /// ```
+    pub fn keys_subset_of(&self, other: &AVLTree<K, V>) -> bool {
+        self.len() <= other.len() && self.difference_keys(other).next().is_none()
+    }
+
+    /// 判断other的每个键是否都在self中，时间复杂度O(n+m)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let a: AVLTree<i32, ()> = vec![1, 2].into_iter().map(|k| (k, ())).collect();
+    /// let b: AVLTree<i32, ()> = vec![1, 2, 3].into_iter().map(|k| (k, ())).collect();
+    /// assert!(b.keys_superset_of(&a));
+    /// assert!(!a.keys_superset_of(&b));
+    /// ```
+    pub fn keys_superset_of(&self, other: &AVLTree<K, V>) -> bool {
+        other.len() <= self.len()
+            && SetOpKeys::new(
+                other.keys(),
+                self.keys(),
+                SetOperation::Difference,
+                &self.cmp,
+            )
+            .next()
+            .is_none()
+    }
+
+    /// 以RangeBounds表示范围的范围迭代器，与range_pair_iter的结果相同
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// use std::collections::Bound;
+    /// let mut tree = AVLTree::new();
+    /// for i in 1..=6 {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// let keys = |iter: Vec<(&i32, &i32)>| iter.into_iter().map(|(k, _)| *k).collect::<Vec<_>>();
+    /// assert_eq!(keys(tree.range(2..=5).collect()), vec![2, 3, 4, 5]);
+    /// assert_eq!(keys(tree.range(2..5).collect()), vec![2, 3, 4]);
+    /// assert_eq!(keys(tree.range(..3).collect()), vec![1, 2]);
+    /// assert_eq!(keys(tree.range(4..).collect()), vec![4, 5, 6]);
+    /// assert_eq!(keys(tree.range(..).collect()), vec![1, 2, 3, 4, 5, 6]);
+    /// assert_eq!(
+    ///     tree.range(2..5).collect::<Vec<_>>(),
+    ///     tree.range_pair_iter(Bound::Included(2), Bound::Excluded(5)).collect::<Vec<_>>()
+    /// );
+    /// ```
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> RangePairIter<'_, K, V> {
+        RangePairIter::new(
+            &self.root,
+            range.start_bound().cloned(),
+            range.end_bound().cloned(),
+            &self.cmp,
+        )
+    }
+
+    /// 按键的降序返回范围内的键值对，上下边界都会被检查
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, i32> = (1..=6).map(|key| (key, key * 10)).collect();
+    /// let keys = |iter: Vec<(&i32, &i32)>| iter.into_iter().map(|(k, _)| *k).collect::<Vec<_>>();
+    /// assert_eq!(keys(tree.range_rev(2..=5).collect()), vec![5, 4, 3, 2]);
+    /// assert_eq!(keys(tree.range_rev(..3).collect()), vec![2, 1]);
+    /// ```
+    pub fn range_rev<R: RangeBounds<K>>(&self, range: R) -> RevRangeIter<'_, K, V> {
+        RevRangeIter::new(self.range(range))
     }
 
-    /// 从AVL树中删除键值对，如果找不到键值对，则忽略
+    /// 按键的升序惰性输出范围内值满足pred的键值对，越过范围的上边界后立即停止
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
-    /// let mut tree = AVLTree::new();
-    /// tree.insert(1, 'a');
-    /// tree.delete(1);
-    /// assert!(tree.is_empty());
-    /// tree.delete(2);
-    /// assert!(tree.is_empty());
+    /// let tree: AVLTree<i32, i32> = (1..=10).map(|key| (key, key * key)).collect();
+    /// let keys: Vec<&i32> = tree.range_filter(3..8, |value| value % 2 == 0).map(|(k, _)| k).collect();
+    /// assert_eq!(keys, vec![&4, &6]);
     /// ```
-    pub fn delete(&mut self, key: K) {
-        if let Some(node) = self.root.take() {
-            self.root = node.delete(key)
-        }
+    pub fn range_filter<R, P>(&self, range: R, mut pred: P) -> impl Iterator<Item = (&K, &V)>
+    where
+        R: RangeBounds<K>,
+        P: FnMut(&V) -> bool,
+    {
+        self.range(range).filter(move |(_, value)| pred(value))
     }
 
-    /// 判断当前AVL树是否为空
+    /// 按键的升序返回范围内所有键的克隆，等价于range(range).map(|(k, _)| k.clone()).collect()
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
-    /// let mut tree: AVLTree<i32, i32> = AVLTree::new();
-    /// assert!(tree.is_empty());
+    /// let tree: AVLTree<i32, char> = vec![(1, 'a'), (2, 'b'), (3, 'c')].into_iter().collect();
+    /// assert_eq!(tree.keys_in_range(2..), vec![2, 3]);
     /// ```
-    pub fn is_empty(&self) -> bool {
-        self.root.is_none()
+    pub fn keys_in_range<R: RangeBounds<K>>(&self, range: R) -> Vec<K> {
+        self.range(range).map(|(key, _)| key.clone()).collect()
     }
 
-    /// 根据键获取相应键值对
+    /// 按键的升序输出范围内的键和值的可变借用，只能修改值，键不变因此无需调整树
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
     /// let mut tree = AVLTree::new();
-    /// tree.insert(1, 'a');
-    /// assert_eq!(tree.get_pair(&1), Some((&1, &'a')));
+    /// for i in 1..=5 {
+    ///     tree.insert(i, i * 10);
+    /// }
+    /// for (_, value) in tree.range_mut(2..4) {
+    ///     *value += 1;
+    /// }
+    /// assert_eq!(tree.values().copied().collect::<Vec<_>>(), vec![10, 21, 31, 40, 50]);
     /// ```
-    pub fn get_pair(&self, key: &K) -> Option<(&K, &V)> {
-        self.root.as_ref().and_then(|node| node.search_pair(key))
+    pub fn range_mut<R: RangeBounds<K>>(&mut self, range: R) -> RangeMut<'_, K, V> {
+        RangeMut::new(
+            &mut self.root,
+            range.start_bound().cloned(),
+            range.end_bound().cloned(),
+            &self.cmp,
+        )
     }
 
-    /// 根据键查找对应的值，找不到返回None，返回值的不可变借用
+    /// 返回按键升序排列的扁平快照，适合读多写少的场景反复做二分查找
+    /// 快照在首次调用时复制所有键值对构建，时间复杂度O(n)，之后缓存在树中，再次调用直接复用；
+    /// 任何对树的修改(包括交出值的可变借用)都会清除缓存，下次调用时重新构建
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
     /// let mut tree = AVLTree::new();
+    /// tree.insert(2, 'b');
     /// tree.insert(1, 'a');
-    /// assert_eq!(tree.get(&1), Some(&'a'));
+    /// let pairs = tree.as_slice_pairs();
+    /// assert_eq!(pairs.as_slice(), &[(1, 'a'), (2, 'b')]);
+    /// assert_eq!(pairs.binary_search(&2), Ok(1));
+    /// assert_eq!(pairs.get(&1), Some(&'a'));
+    /// tree.insert(3, 'c');
+    /// assert_eq!(tree.as_slice_pairs().get(&3), Some(&'c'));
     /// ```
-    pub fn get(&self, key: &K) -> Option<&V> {
-        self.root.as_ref().and_then(|node| node.search(key))
+    pub fn as_slice_pairs(&self) -> SortedPairs<'_, K, V>
+    where
+        V: Clone,
+    {
+        let pairs = self.root.pairs(|| {
+            self.iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        });
+        SortedPairs::new(pairs, &self.cmp)
     }
 
-    /// 据键查找对应的值，找不到返回默认值
+    /// 将AVL树打印成缩进的多行字符串，每行一个节点，缩进表示深度，子节点前以L或R标明是左孩子还是右孩子
+    /// 使用显式栈按前序遍历生成，不会因为树深而递归过深；空树返回空字符串
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
     /// let mut tree = AVLTree::new();
+    /// tree.insert(2, 'b');
     /// tree.insert(1, 'a');
-    /// assert_eq!(tree.get_or(&1, &'z'), &'a');
-    /// assert_eq!(tree.get_or(&2, &'z'), &'z');
+    /// tree.insert(3, 'c');
+    /// assert_eq!(tree.to_pretty_string(), "2: b\n  L 1: a\n  R 3: c\n");
     /// ```
-    pub fn get_or<'a>(&'a self, key: &K, default: &'a V) -> &'a V {
-        self.get(key).map_or(default, |data| data)
+    pub fn to_pretty_string(&self) -> String
+    where
+        K: ToString,
+        V: ToString,
+    {
+        let mut output = String::new();
+        let mut stack: Vec<(&Node<K, V>, usize, &str)> =
+            self.root.iter().map(|root| (&**root, 0, "")).collect();
+        while let Some((node, depth, side)) = stack.pop() {
+            let (key, value) = node.pair();
+            for _ in 0..depth {
+                output.push_str("  ");
+            }
+            output.push_str(side);
+            output.push_str(&key.to_string());
+            output.push_str(": ");
+            output.push_str(&value.to_string());
+            output.push('\n');
+            // 先压右孩子，保证左孩子先输出
+            if let Some(right) = node.right() {
+                stack.push((right, depth + 1, "R "));
+            }
+            if let Some(left) = node.left() {
+                stack.push((left, depth + 1, "L "));
+            }
+        }
+        output
     }
 
-    /// 查找是否存在键值对
+    /// 前序遍历迭代器
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
     /// let mut tree = AVLTree::new();
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
     /// tree.insert(1, 'a');
-    /// assert_eq!(tree.contains(&1), true);
-    /// assert_eq!(tree.contains(&2), false);
+    /// let res: Vec<(&i32, &char)> = tree.preorder_iter().collect();
+    /// assert_eq!(res, vec![(&2, &'b'), (&1, &'a'), (&3, &'c')]);
     /// ```
-    pub fn contains(&self, key: &K) -> bool {
-        self.get(key).is_some()
+    pub fn preorder_iter(&self) -> TraverseIter<'_, K, V> {
+        TraverseIter::new(&self.root, TraverseOrder::Pre)
     }
 
-    /// 返回AVL树中的最小键值对
+    /// 中序遍历迭代器
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
@@ -120,13 +2125,14 @@ impl<K: PartialOrd + Clone, V> AVLTree<K, V> {
     /// tree.insert(3, 'c');
     /// tree.insert(2, 'b');
     /// tree.insert(1, 'a');
-    /// assert_eq!(tree.min_pair(), Some((&1, &'a')));
+    /// let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b'), (&3, &'c')]);
     /// ```
-    pub fn min_pair(&self) -> Option<(&K, &V)> {
-        self.root.as_ref().map(|node| node.min_pair())
+    pub fn inorder_iter(&self) -> TraverseIter<'_, K, V> {
+        TraverseIter::new(&self.root, TraverseOrder::In)
     }
 
-    /// 返回AVL树中的最大键值对
+    /// 后序遍历迭代器
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
@@ -134,29 +2140,29 @@ impl<K: PartialOrd + Clone, V> AVLTree<K, V> {
     /// tree.insert(3, 'c');
     /// tree.insert(2, 'b');
     /// tree.insert(1, 'a');
-    /// assert_eq!(tree.max_pair(), Some((&3, &'c')));
+    /// let res: Vec<(&i32, &char)> = tree.postorder_iter().collect();
+    /// assert_eq!(res, vec![(&1, &'a'), (&3, &'c'), (&2, &'b')]);
     /// ```
-    pub fn max_pair(&self) -> Option<(&K, &V)> {
-        self.root.as_ref().map(|node| node.max_pair())
+    pub fn postorder_iter(&self) -> TraverseIter<'_, K, V> {
+        TraverseIter::new(&self.root, TraverseOrder::Post)
     }
 
-    /// 判断是否为AVL树，空树不算AVL树
+    /// 层序遍历迭代器
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
     /// let mut tree = AVLTree::new();
-    /// assert!(!tree.is_avl_tree());
+    /// tree.insert(3, 'c');
+    /// tree.insert(2, 'b');
     /// tree.insert(1, 'a');
-    /// assert!(tree.is_avl_tree());
+    /// let res: Vec<(&i32, &char)> = tree.levelorder_iter().collect();
+    /// assert_eq!(res, vec![(&2, &'b'), (&1, &'a'), (&3, &'c')]);
     /// ```
-    pub fn is_avl_tree(&self) -> bool {
-        if self.root.is_none() {
-            return false;
-        }
-        Node::is_avl_tree(&self.root)
+    pub fn levelorder_iter(&self) -> TraverseIter<'_, K, V> {
+        TraverseIter::new(&self.root, TraverseOrder::Level)
     }
 
-    ///返回第一个大于key的键值对
+    /// 层序遍历，同时记录每个节点的深度(根节点为第0层)，返回(深度, 键, 值)的列表
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
@@ -164,167 +2170,239 @@ impl<K: PartialOrd + Clone, V> AVLTree<K, V> {
     /// tree.insert(3, 'c');
     /// tree.insert(2, 'b');
     /// tree.insert(1, 'a');
-    /// assert_eq!(tree.successor(&1), Some((&2, &'b')));
-    /// assert_eq!(tree.successor(&0), Some((&1, &'a')));
-    /// assert_eq!(tree.successor(&3), None);
+    /// let res = tree.levelorder_with_depth();
+    /// assert_eq!(res, vec![(0, &2, &'b'), (1, &1, &'a'), (1, &3, &'c')]);
     /// ```
-    pub fn successor(&self, key: &K) -> Option<(&K, &V)> {
-        self.root.as_ref().and_then(|node| node.successor(key))
+    pub fn levelorder_with_depth(&self) -> Vec<(usize, &K, &V)> {
+        let mut res = Vec::with_capacity(self.len());
+        let mut queue: VecDeque<(usize, &Node<K, V>)> =
+            self.root.iter().map(|node| (0, &**node)).collect();
+        while let Some((depth, node)) = queue.pop_front() {
+            let (key, value) = node.pair();
+            res.push((depth, key, value));
+            queue.extend(node.left().iter().map(|left| (depth + 1, &**left)));
+            queue.extend(node.right().iter().map(|right| (depth + 1, &**right)));
+        }
+        res
     }
 
-    ///返回第一个小于key的键值对
+    /// 返回第depth层(根节点为第0层)的节点个数
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
     /// let mut tree = AVLTree::new();
-    /// tree.insert(3, 'c');
     /// tree.insert(2, 'b');
     /// tree.insert(1, 'a');
-    /// assert_eq!(tree.predecessor(&3), Some((&2, &'b')));
-    /// assert_eq!(tree.predecessor(&5), Some((&3, &'c')));
-    /// assert_eq!(tree.predecessor(&1), None);
+    /// tree.insert(3, 'c');
+    /// assert_eq!(tree.width_at_depth(0), 1);
+    /// assert_eq!(tree.width_at_depth(1), 2);
+    /// assert_eq!(tree.width_at_depth(2), 0);
     /// ```
-    pub fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
-        self.root.as_ref().and_then(|node| node.predecessor(key))
+    pub fn width_at_depth(&self, depth: usize) -> usize {
+        Node::width_at_depth(&self.root, depth)
     }
 
-    /// 范围迭代器
+    /// 按前序返回每个节点的键及其左右孩子的键，用于导出树的结构
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
-    /// use std::collections::Bound;
     /// let mut tree = AVLTree::new();
-    /// tree.insert(3, 'c');
     /// tree.insert(2, 'b');
     /// tree.insert(1, 'a');
-    /// let res: Vec<(&i32, &char)> = tree.range_pair_iter(Bound::Unbounded, Bound::Unbounded).collect();
-    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b'), (&3, &'c')]);
-    /// let res: Vec<(&i32, &char)> = tree.range_pair_iter(Bound::Included(0), Bound::Included(2)).collect();
-    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b')]);
-    /// let res: Vec<(&i32, &char)> = tree.range_pair_iter(Bound::Excluded(1), Bound::Excluded(3)).collect();
-    /// assert_eq!(res, vec![(&2, &'b')]);
+    /// assert_eq!(tree.to_adjacency_list(), vec![(2, Some(1), None), (1, None, None)]);
     /// ```
-    pub fn range_pair_iter(&self, min: Bound<K>, max: Bound<K>) -> RangePairIter<K, V> {
-        RangePairIter::new(self, min, max)
+    pub fn to_adjacency_list(&self) -> Vec<(K, Option<K>, Option<K>)> {
+        let mut buf = Vec::new();
+        Node::adjacency(&self.root, &mut buf);
+        buf
     }
 
-    /// 前序遍历迭代器
+    ///合并两棵树的有序键序列，shared为true时保留other中也存在的键，否则保留other中不存在的键
+    fn retain_by_keys_of(&mut self, other: &AVLTree<K, V>, shared: bool) {
+        let cmp = self.cmp.clone();
+        let mut others = other.keys().peekable();
+        let survivors: Vec<(K, V)> = IntoIter::new(self.root.take())
+            .filter(|(key, _)| {
+                while others
+                    .next_if(|&other| cmp.compare(other, key).is_lt())
+                    .is_some()
+                {}
+                others
+                    .peek()
+                    .is_some_and(|&other| cmp.compare(other, key).is_eq())
+                    == shared
+            })
+            .collect();
+        self.set_sorted(survivors);
+    }
+
+    ///由按键升序排列且键不重复的键值对构建平衡的AVL树
+    fn from_sorted(pairs: Vec<(K, V)>) -> Self {
+        let mut tree = Self::new();
+        tree.set_sorted(pairs);
+        tree
+    }
+
+    ///用按当前比较规则升序排列且键不重复的键值对重新构建平衡的树，替换原有的根节点
+    fn set_sorted(&mut self, pairs: Vec<(K, V)>) {
+        let n = pairs.len();
+        self.set_root(Node::from_sorted(&mut pairs.into_iter(), n));
+    }
+}
+
+impl<K: Ord, V> AVLTree<K, V> {
+    /// 返回第一个大于等于key的键值对，key存在时返回其本身
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
     /// let mut tree = AVLTree::new();
-    /// tree.insert(3, 'c');
-    /// tree.insert(2, 'b');
     /// tree.insert(1, 'a');
-    /// let res: Vec<(&i32, &char)> = tree.preorder_iter().collect();
-    /// assert_eq!(res, vec![(&2, &'b'), (&1, &'a'), (&3, &'c')]);
+    /// tree.insert(3, 'c');
+    /// assert_eq!(tree.entry_at_or_after(&1), Some((&1, &'a')));
+    /// assert_eq!(tree.entry_at_or_after(&2), Some((&3, &'c')));
+    /// assert_eq!(tree.entry_at_or_after(&4), None);
     /// ```
-    pub fn preorder_iter(&self) -> TraverseIter<K, V> {
-        let pre_order = self.prev_order();
-        let mut queue = VecDeque::new();
-        for key in pre_order {
-            if let Some(p) = self.get_pair(&key) {
-                queue.push_back(p);
-            }
-        }
-        TraverseIter::new(queue)
+    pub fn entry_at_or_after(&self, key: &K) -> Option<(&K, &V)> {
+        self.root
+            .as_ref()
+            .and_then(|node| node.ceiling(key, &self.cmp))
     }
 
-    /// 中序遍历迭代器
+    /// 返回最后一个小于等于key的键值对，key存在时返回其本身
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
     /// let mut tree = AVLTree::new();
-    /// tree.insert(3, 'c');
-    /// tree.insert(2, 'b');
     /// tree.insert(1, 'a');
-    /// let res: Vec<(&i32, &char)> = tree.inorder_iter().collect();
-    /// assert_eq!(res, vec![(&1, &'a'), (&2, &'b'), (&3, &'c')]);
+    /// tree.insert(3, 'c');
+    /// assert_eq!(tree.entry_at_or_before(&3), Some((&3, &'c')));
+    /// assert_eq!(tree.entry_at_or_before(&2), Some((&1, &'a')));
+    /// assert_eq!(tree.entry_at_or_before(&0), None);
     /// ```
-    pub fn inorder_iter(&self) -> TraverseIter<K, V> {
-        let in_order = self.in_order();
-        let mut queue = VecDeque::new();
-        for key in in_order {
-            if let Some(p) = self.get_pair(&key) {
-                queue.push_back(p);
-            }
-        }
-        TraverseIter::new(queue)
+    pub fn entry_at_or_before(&self, key: &K) -> Option<(&K, &V)> {
+        self.root
+            .as_ref()
+            .and_then(|node| node.floor(key, &self.cmp))
     }
 
-    /// 后序遍历迭代器
+    /// 返回最大的小于等于key的键值对，与predecessor不同，key存在时返回其本身
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
     /// let mut tree = AVLTree::new();
+    /// tree.insert(1, 'a');
     /// tree.insert(3, 'c');
-    /// tree.insert(2, 'b');
+    /// assert_eq!(tree.floor(&3), Some((&3, &'c')));
+    /// assert_eq!(tree.floor(&2), Some((&1, &'a')));
+    /// assert_eq!(tree.floor(&0), None);
+    /// ```
+    pub fn floor(&self, key: &K) -> Option<(&K, &V)> {
+        self.root
+            .as_ref()
+            .and_then(|node| node.floor(key, &self.cmp))
+    }
+
+    /// 返回最小的大于等于key的键值对，与successor不同，key存在时返回其本身
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
     /// tree.insert(1, 'a');
-    /// let res: Vec<(&i32, &char)> = tree.postorder_iter().collect();
-    /// assert_eq!(res, vec![(&1, &'a'), (&3, &'c'), (&2, &'b')]);
+    /// tree.insert(3, 'c');
+    /// assert_eq!(tree.ceiling(&1), Some((&1, &'a')));
+    /// assert_eq!(tree.ceiling(&2), Some((&3, &'c')));
+    /// assert_eq!(tree.ceiling(&4), None);
+    /// ```
+    pub fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+        self.root
+            .as_ref()
+            .and_then(|node| node.ceiling(key, &self.cmp))
+    }
+
+    /// 假设pred对按键升序排列的键值对先返回true后返回false，返回第一个使pred返回false的键值对，全部为true时返回None
+    /// 与切片的partition_point相同，沿树根据pred的结果选择分支下降，时间复杂度O(log n)
+    /// # Example
     /// ```
-    pub fn postorder_iter(&self) -> TraverseIter<K, V> {
-        let post_order = self.post_order();
-        let mut queue = VecDeque::new();
-        for key in post_order {
-            if let Some(p) = self.get_pair(&key) {
-                queue.push_back(p);
+    /// use an_ok_avl_tree::AVLTree;
+    /// let tree: AVLTree<i32, char> = vec![(1, 'a'), (3, 'c'), (5, 'e')].into_iter().collect();
+    /// assert_eq!(tree.partition_point(|&key| key < 3), Some((&3, &'c')));
+    /// assert_eq!(tree.partition_point(|&key| key < 4), Some((&5, &'e')));
+    /// assert_eq!(tree.partition_point(|_| true), None);
+    /// assert_eq!(tree.partition_point(|_| false), Some((&1, &'a')));
+    /// ```
+    pub fn partition_point<P: FnMut(&K) -> bool>(&self, mut pred: P) -> Option<(&K, &V)> {
+        let mut found = None;
+        let mut link: &Link<K, V> = &self.root;
+        while let Some(node) = link {
+            let (key, value) = node.pair();
+            if pred(key) {
+                link = node.right();
+            } else {
+                found = Some((key, value));
+                link = node.left();
             }
         }
-        TraverseIter::new(queue)
+        found
     }
 
-    /// 层序遍历迭代器
+    /// 返回floor和ceiling中按distance(key, 候选键)距离key最近的键值对，距离相等时返回较小的键(floor)
+    /// key存在时返回其本身，时间复杂度O(log n)
     /// # Example
     /// ```
     /// use an_ok_avl_tree::AVLTree;
     /// let mut tree = AVLTree::new();
-    /// tree.insert(3, 'c');
-    /// tree.insert(2, 'b');
-    /// tree.insert(1, 'a');
-    /// let res: Vec<(&i32, &char)> = tree.levelorder_iter().collect();
-    /// assert_eq!(res, vec![(&2, &'b'), (&1, &'a'), (&3, &'c')]);
+    /// tree.insert(10, 'a');
+    /// tree.insert(20, 'b');
+    /// let distance = |a: &i32, b: &i32| (a - b).abs();
+    /// assert_eq!(tree.nearest(&12, distance), Some((&10, &'a')));
+    /// assert_eq!(tree.nearest(&18, distance), Some((&20, &'b')));
+    /// assert_eq!(tree.nearest(&15, distance), Some((&10, &'a')));
     /// ```
-    pub fn levelorder_iter(&self) -> TraverseIter<K, V> {
-        let level_order = self.level_order();
-        let mut queue = VecDeque::new();
-        for key in level_order {
-            if let Some(p) = self.get_pair(&key) {
-                queue.push_back(p);
+    pub fn nearest<D: Ord, F: Fn(&K, &K) -> D>(&self, key: &K, distance: F) -> Option<(&K, &V)> {
+        match (self.floor(key), self.ceiling(key)) {
+            (Some(below), Some(above)) => {
+                if distance(key, above.0) < distance(key, below.0) {
+                    Some(above)
+                } else {
+                    Some(below)
+                }
             }
+            (below, above) => below.or(above),
         }
-        TraverseIter::new(queue)
     }
+}
 
-    ///前序遍历
-    fn prev_order(&self) -> Vec<K> {
-        let mut buf = Vec::new();
-        Node::prev_order(&self.root, &mut buf);
-        buf
-    }
+// 多路归并时堆中保存的各数据源的当前键值对，键越小、数据源序号越小越先出堆
+struct MergeHead<K, V> {
+    key: K,
+    value: V,
+    source: usize,
+}
 
-    ///中序遍历
-    fn in_order(&self) -> Vec<K> {
-        let mut buf = Vec::new();
-        Node::in_order(&self.root, &mut buf);
-        buf
+impl<K: Ord, V> Ord for MergeHead<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap是大顶堆，因此反向比较
+        other
+            .key
+            .cmp(&self.key)
+            .then_with(|| other.source.cmp(&self.source))
     }
+}
 
-    ///后序遍历
-    fn post_order(&self) -> Vec<K> {
-        let mut buf = Vec::new();
-        Node::post_order(&self.root, &mut buf);
-        buf
+impl<K: Ord, V> PartialOrd for MergeHead<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
+}
 
-    ///层序遍历
-    fn level_order(&self) -> Vec<K> {
-        let mut buf = Vec::new();
-        Node::level_order(&self.root, &mut buf);
-        buf
+impl<K: Ord, V> PartialEq for MergeHead<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
     }
 }
 
+impl<K: Ord, V> Eq for MergeHead<K, V> {}
+
 /// 将AVL树打印成字符串
 /// # Example
 /// ```
@@ -333,7 +2411,8 @@ impl<K: PartialOrd + Clone, V> AVLTree<K, V> {
 /// tree.insert(1, 'a');
 /// assert_eq!(tree.to_string(), "[K: 1, V: a, L: Ø, R: Ø]".to_string());
 /// ```
-impl<K: PartialOrd + ToString, V: ToString> ToString for AVLTree<K, V> {
+#[allow(clippy::to_string_trait_impl)]
+impl<K: Ord + ToString, V: ToString> ToString for AVLTree<K, V> {
     fn to_string(&self) -> String {
         self.root
             .as_ref()
@@ -341,8 +2420,263 @@ impl<K: PartialOrd + ToString, V: ToString> ToString for AVLTree<K, V> {
     }
 }
 
-impl<K: PartialOrd + Clone, V> Default for AVLTree<K, V> {
+/// 克隆时逐个节点复制，保持原树的结构和高度不变
+/// # Example
+/// ```
+/// use an_ok_avl_tree::AVLTree;
+/// let mut tree = AVLTree::new();
+/// tree.insert(1, 'a');
+/// let cloned = tree.clone();
+/// tree.insert(0, 'z');
+/// assert_eq!(cloned.min_pair(), Some((&1, &'a')));
+/// ```
+impl<K: Clone, V: Clone> Clone for AVLTree<K, V> {
+    fn clone(&self) -> Self {
+        self.with_root(self.root.clone())
+    }
+
+    // 与source形状相同的部分原地覆盖键值，只为多出的节点分配内存，多余的节点被释放
+    fn clone_from(&mut self, source: &Self) {
+        Node::clone_link_from(&mut self.root, &source.root);
+        self.cmp = source.cmp.clone();
+        self.bounds.clone_from(&source.bounds);
+    }
+}
+
+/// 按键的升序将AVL树格式化为映射的形式
+/// # Example
+/// ```
+/// use an_ok_avl_tree::AVLTree;
+/// let mut tree = AVLTree::new();
+/// tree.insert(2, 'b');
+/// tree.insert(1, 'a');
+/// assert_eq!(format!("{:?}", tree), "{1: 'a', 2: 'b'}");
+/// ```
+impl<K: Debug, V: Debug> Debug for AVLTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(Iter::new(&self.root)).finish()
+    }
+}
+
+// 用显式栈逐个释放节点：先取出节点的左右子树再释放节点本身，释放过程不会递归
+impl<K, V> Drop for AVLTree<K, V> {
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<K, V>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.take_left());
+            stack.extend(node.take_right());
+        }
+    }
+}
+
+/// 按键的升序比较两棵树的键值对，与树的形状无关
+/// # Example
+/// ```
+/// use an_ok_avl_tree::AVLTree;
+/// let a: AVLTree<i32, char> = vec![(1, 'a'), (2, 'b'), (3, 'c')].into_iter().collect();
+/// let b: AVLTree<i32, char> = vec![(3, 'c'), (2, 'b'), (1, 'a')].into_iter().collect();
+/// assert_eq!(a, b);
+/// ```
+impl<K: Ord + Clone, V: PartialEq> PartialEq for AVLTree<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Ord + Clone, V: Eq> Eq for AVLTree<K, V> {}
+
+/// 按键的升序依次哈希键值对，与树的形状无关，相等的两棵树哈希值相同
+/// # Example
+/// ```
+/// use an_ok_avl_tree::AVLTree;
+/// use std::collections::HashSet;
+/// let a: AVLTree<i32, char> = vec![(1, 'a'), (2, 'b')].into_iter().collect();
+/// let b: AVLTree<i32, char> = vec![(2, 'b'), (1, 'a')].into_iter().collect();
+/// let mut set = HashSet::new();
+/// set.insert(a);
+/// assert!(set.contains(&b));
+/// ```
+impl<K: Ord + Clone + Hash, V: Hash> Hash for AVLTree<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(Node::size(&self.root) as usize);
+        for pair in self.iter() {
+            pair.hash(state);
+        }
+    }
+}
+
+/// 消耗AVL树，按键的升序返回拥有所有权的键值对
+/// # Example
+/// ```
+/// use an_ok_avl_tree::AVLTree;
+/// let mut tree = AVLTree::new();
+/// tree.insert(2, 'b');
+/// tree.insert(1, 'a');
+/// let res: Vec<(i32, char)> = tree.into_iter().collect();
+/// assert_eq!(res, vec![(1, 'a'), (2, 'b')]);
+/// ```
+impl<K: Ord + Clone, V> IntoIterator for AVLTree<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        IntoIter::new(self.root.take())
+    }
+}
+
+/// 由键值对序列构建AVL树，键重复时保留后出现的值
+/// # Example
+/// ```
+/// use an_ok_avl_tree::AVLTree;
+/// let tree: AVLTree<i32, char> = vec![(2, 'b'), (1, 'a'), (2, 'c')].into_iter().collect();
+/// assert_eq!(tree.get(&1), Some(&'a'));
+/// assert_eq!(tree.get(&2), Some(&'c'));
+/// ```
+impl<K: Ord + Clone, V> FromIterator<(K, V)> for AVLTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = AVLTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+/// 批量插入键值对，键重复时保留后出现的值
+/// # Example
+/// ```
+/// use an_ok_avl_tree::AVLTree;
+/// let mut tree = AVLTree::new();
+/// tree.insert(1, 'a');
+/// tree.extend(vec![(1, 'z'), (2, 'b')]);
+/// assert_eq!(tree.get(&1), Some(&'z'));
+/// assert_eq!(tree.get(&2), Some(&'b'));
+/// ```
+impl<K: Ord + Clone, V> Extend<(K, V)> for AVLTree<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+/// 由BTreeMap构建AVL树，BTreeMap的键已按Ord升序排列且不重复，直接构建平衡的树，时间复杂度O(n)
+/// # Example
+/// ```
+/// use an_ok_avl_tree::AVLTree;
+/// use std::collections::BTreeMap;
+/// let mut map = BTreeMap::new();
+/// map.insert(2, 'b');
+/// map.insert(1, 'a');
+/// let tree = AVLTree::from(map);
+/// assert_eq!(tree.get(&1), Some(&'a'));
+/// assert_eq!(tree.len(), 2);
+/// ```
+impl<K: Ord + Clone, V> From<BTreeMap<K, V>> for AVLTree<K, V> {
+    fn from(map: BTreeMap<K, V>) -> Self {
+        Self::from_sorted(map.into_iter().collect())
+    }
+}
+
+impl<K: Ord + Clone, V> Default for AVLTree<K, V> {
     fn default() -> Self {
         Self::new()
     }
 }
+
+/// 按键读取对应的值，键不存在时panic，需要处理不存在的情况时使用get
+/// # Example
+/// ```
+/// use an_ok_avl_tree::AVLTree;
+/// let mut tree = AVLTree::new();
+/// tree.insert(1, 'a');
+/// assert_eq!(tree[&1], 'a');
+/// ```
+impl<K: Ord + Clone, V> Index<&K> for AVLTree<K, V> {
+    type Output = V;
+
+    fn index(&self, key: &K) -> &V {
+        self.get(key).expect("key not found in AVLTree")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 构造一棵只有右子树的退化树: 1 -> 2 -> 3
+    fn degenerate_tree() -> AVLTree<i32, char> {
+        let three = Node::with_children(3, 'c', None, None);
+        let two = Node::with_children(2, 'b', None, Some(Box::new(three)));
+        let one = Node::with_children(1, 'a', None, Some(Box::new(two)));
+        AVLTree::from_root(Some(Box::new(one)))
+    }
+
+    #[test]
+    fn is_bst_ignores_balance() {
+        let tree = degenerate_tree();
+        assert!(tree.is_bst());
+        assert!(!tree.is_avl_tree());
+
+        let three = Node::with_children(3, 'c', None, None);
+        let two = Node::with_children(2, 'b', Some(Box::new(three)), None);
+        let tree = AVLTree::from_root(Some(Box::new(two)));
+        assert!(!tree.is_bst());
+    }
+
+    #[test]
+    fn validate_reports_first_violation() {
+        assert_eq!(
+            degenerate_tree().validate(),
+            Err(AvlError::BalanceViolation {
+                key: 1,
+                balance: -2
+            })
+        );
+
+        let three = Node::with_children(3, 'c', None, None);
+        let two = Node::with_children(2, 'b', Some(Box::new(three)), None);
+        let tree = AVLTree::from_root(Some(Box::new(two)));
+        assert_eq!(tree.validate(), Err(AvlError::UnorderedKeys { key: 2 }));
+
+        // 只有隔代的键违反顺序：7位于5的左子树中，但与直接父节点3的顺序正确
+        let seven = Node::with_children(7, 'g', None, None);
+        let three = Node::with_children(3, 'c', None, Some(Box::new(seven)));
+        let five = Node::with_children(5, 'e', Some(Box::new(three)), None);
+        let tree = AVLTree::from_root(Some(Box::new(five)));
+        assert!(!tree.is_bst());
+        assert_eq!(tree.validate(), Err(AvlError::UnorderedKeys { key: 3 }));
+        assert!(!tree.is_avl_tree());
+
+        // 取走孩子后不更新节点保存的高度和子树大小
+        let one = Node::with_children(1, 'a', None, None);
+        let mut two = Node::with_children(2, 'b', Some(Box::new(one)), None);
+        two.take_left();
+        let tree = AVLTree::from_root(Some(Box::new(two)));
+        assert_eq!(
+            tree.validate(),
+            Err(AvlError::HeightMismatch {
+                key: 2,
+                stored: 2,
+                actual: 1
+            })
+        );
+
+        // 取走右孩子后左子树仍保持高度不变，只有子树大小与实际不符
+        let one = Node::with_children(1, 'a', None, None);
+        let three = Node::with_children(3, 'c', None, None);
+        let mut two = Node::with_children(2, 'b', Some(Box::new(one)), Some(Box::new(three)));
+        two.take_right();
+        let tree = AVLTree::from_root(Some(Box::new(two)));
+        assert_eq!(
+            tree.validate(),
+            Err(AvlError::SizeMismatch {
+                key: 2,
+                stored: 3,
+                actual: 2
+            })
+        );
+
+        let tree: AVLTree<i32, char> = (0..50).map(|key| (key, 'x')).collect();
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(AVLTree::<i32, char>::new().validate(), Ok(()));
+    }
+}
@@ -0,0 +1,56 @@
+use alloc::sync::Arc;
+use core::cmp::Ordering;
+use core::ops::Bound;
+
+// 自定义的比较函数，多棵树(如split_off得到的树)共享同一个
+type CompareFn<K> = Arc<dyn Fn(&K, &K) -> Ordering + Send + Sync>;
+
+// 键的比较规则，树中所有的下降方向和范围边界都由它决定
+// 默认使用键自身的Ord，new_by构造的树使用传入的比较函数
+pub enum Comparator<K> {
+    Natural,
+    Custom(CompareFn<K>),
+}
+
+impl<K: Ord> Comparator<K> {
+    // 比较两个键
+    #[inline]
+    pub fn compare(&self, a: &K, b: &K) -> Ordering {
+        match self {
+            Comparator::Natural => a.cmp(b),
+            Comparator::Custom(cmp) => cmp(a, b),
+        }
+    }
+
+    // 是否为默认的比较规则
+    pub fn is_natural(&self) -> bool {
+        matches!(self, Comparator::Natural)
+    }
+
+    // 检查键是否满足下边界
+    pub fn above_lower_bound(&self, key: &K, from: Bound<&K>) -> bool {
+        match from {
+            Bound::Included(from) => self.compare(key, from) != Ordering::Less,
+            Bound::Excluded(from) => self.compare(key, from) == Ordering::Greater,
+            Bound::Unbounded => true,
+        }
+    }
+
+    // 检查键是否满足上边界
+    pub fn below_upper_bound(&self, key: &K, to: Bound<&K>) -> bool {
+        match to {
+            Bound::Included(to) => self.compare(key, to) != Ordering::Greater,
+            Bound::Excluded(to) => self.compare(key, to) == Ordering::Less,
+            Bound::Unbounded => true,
+        }
+    }
+}
+
+impl<K> Clone for Comparator<K> {
+    fn clone(&self) -> Self {
+        match self {
+            Comparator::Natural => Comparator::Natural,
+            Comparator::Custom(cmp) => Comparator::Custom(Arc::clone(cmp)),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use crate::AVLTree;
+use core::fmt::{self, Debug, Display};
+
+/// AVLTree中某个键对应的位置，可能已被占用，也可能为空
+/// with_key_bounds构造的树只为区间内的键返回Entry，因此空位置插入时总能成功
+pub enum Entry<'a, K, V> {
+    /// 键不存在
+    Vacant(VacantEntry<'a, K, V>),
+    /// 键已存在
+    Occupied(OccupiedEntry<'a, K, V>),
+}
+
+/// 不存在的键对应的位置，持有键和树的可变借用
+pub struct VacantEntry<'a, K, V> {
+    key: K,
+    tree: &'a mut AVLTree<K, V>,
+}
+
+/// 已存在的键对应的位置，持有树中的键和值的可变借用
+pub struct OccupiedEntry<'a, K, V> {
+    key: &'a K,
+    value: &'a mut V,
+}
+
+/// try_insert遇到已存在的键时返回的错误，持有已存在的位置和被拒绝插入的键值对
+pub struct OccupiedError<'a, K, V> {
+    /// 已存在的键对应的位置
+    pub entry: OccupiedEntry<'a, K, V>,
+    /// 被拒绝插入的键
+    pub key: K,
+    /// 被拒绝插入的值
+    pub value: V,
+}
+
+/// try_insert拒绝插入时返回的错误
+pub enum TryInsertError<'a, K, V> {
+    /// 键已存在
+    Occupied(OccupiedError<'a, K, V>),
+    /// 键在with_key_bounds指定的区间外，持有被拒绝插入的键值对
+    OutOfBounds(K, V),
+}
+
+impl<'a, K: Ord + Clone, V> Entry<'a, K, V> {
+    /// 返回该位置对应的键
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree: AVLTree<i32, char> = AVLTree::new();
+    /// assert_eq!(tree.entry(1).unwrap().key(), &1);
+    /// ```
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Vacant(entry) => entry.key(),
+            Entry::Occupied(entry) => entry.key(),
+        }
+    }
+
+    /// 键不存在时插入default，返回值的可变借用
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// *tree.entry(1).unwrap().or_insert(0) += 10;
+    /// *tree.entry(1).unwrap().or_insert(0) += 10;
+    /// assert_eq!(tree.get(&1), Some(&20));
+    /// ```
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// 键不存在时插入default()的结果，default只在键不存在时调用，返回值的可变借用
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree: AVLTree<i32, String> = AVLTree::new();
+    /// tree.entry(1).unwrap().or_insert_with(|| "a".to_string()).push('b');
+    /// assert_eq!(tree.get(&1), Some(&"ab".to_string()));
+    /// ```
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+
+    /// 键不存在时以键为参数调用default，插入其结果，返回值的可变借用
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree = AVLTree::new();
+    /// tree.entry(3).unwrap().or_insert_with_key(|key| key * 100);
+    /// assert_eq!(tree.get(&3), Some(&300));
+    /// ```
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+            Entry::Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    /// 键不存在时插入V::default()，返回值的可变借用
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::AVLTree;
+    /// let mut tree: AVLTree<&str, usize> = AVLTree::new();
+    /// for word in "a b a c b a".split(' ') {
+    ///     *tree.entry(word).unwrap().or_default() += 1;
+    /// }
+    /// assert_eq!(tree.get(&"a"), Some(&3));
+    /// assert_eq!(tree.get(&"b"), Some(&2));
+    /// assert_eq!(tree.get(&"c"), Some(&1));
+    /// ```
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K: Ord + Clone, V> VacantEntry<'a, K, V> {
+    pub(crate) fn new(key: K, tree: &'a mut AVLTree<K, V>) -> Self {
+        VacantEntry { key, tree }
+    }
+
+    /// 返回待插入的键
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// 插入值，返回插入后值的可变借用
+    pub fn insert(self, value: V) -> &'a mut V {
+        let key = self.key.clone();
+        // 创建空位置时已经检查过键的区间
+        self.tree.insert_unchecked(self.key, value);
+        self.tree.get_mut(&key).expect("inserted key not found")
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    pub(crate) fn new(key: &'a K, value: &'a mut V) -> Self {
+        OccupiedEntry { key, value }
+    }
+
+    /// 返回树中的键
+    pub fn key(&self) -> &K {
+        self.key
+    }
+
+    /// 返回值的不可变借用
+    pub fn get(&self) -> &V {
+        self.value
+    }
+
+    /// 返回值的可变借用
+    pub fn get_mut(&mut self) -> &mut V {
+        self.value
+    }
+
+    /// 消耗该位置，返回与树的借用生命周期相同的值的可变借用
+    pub fn into_mut(self) -> &'a mut V {
+        self.value
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for OccupiedError<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedError")
+            .field("key", self.entry.key())
+            .field("old_value", self.entry.get())
+            .field("new_value", &self.value)
+            .finish()
+    }
+}
+
+impl<K: Debug, V: Debug> Display for OccupiedError<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to insert {:?}, key {:?} already exists with value {:?}",
+            self.value,
+            self.entry.key(),
+            self.entry.get()
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Debug, V: Debug> std::error::Error for OccupiedError<'_, K, V> {}
+
+impl<K: Debug, V: Debug> Debug for TryInsertError<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryInsertError::Occupied(err) => f.debug_tuple("Occupied").field(err).finish(),
+            TryInsertError::OutOfBounds(key, value) => f
+                .debug_tuple("OutOfBounds")
+                .field(key)
+                .field(value)
+                .finish(),
+        }
+    }
+}
+
+impl<K: Debug, V: Debug> Display for TryInsertError<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryInsertError::Occupied(err) => Display::fmt(err, f),
+            TryInsertError::OutOfBounds(key, value) => write!(
+                f,
+                "failed to insert {:?}, key {:?} is outside the tree's key bounds",
+                value, key
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Debug, V: Debug> std::error::Error for TryInsertError<'_, K, V> {}
@@ -0,0 +1,49 @@
+use core::fmt::{self, Debug, Display};
+
+/// validate发现的第一处违反AVL树性质的位置，key为出错节点的键
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AvlError<K> {
+    /// 孩子的键不在它所处位置允许的范围内：左孩子的键不小于当前节点的键，或右孩子的键不大于当前节点的键，
+    /// 或者孩子的键越过了更上层祖先的键，key为该孩子的父节点的键
+    UnorderedKeys { key: K },
+    /// 节点中保存的高度与由子树计算出的实际高度不一致
+    HeightMismatch { key: K, stored: u32, actual: u32 },
+    /// 节点中保存的子树大小与实际的节点个数不一致
+    SizeMismatch { key: K, stored: u32, actual: u32 },
+    /// 左右子树的高度差超出[-1, 1]
+    BalanceViolation { key: K, balance: i32 },
+}
+
+impl<K: Debug> Display for AvlError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AvlError::UnorderedKeys { key } => {
+                write!(f, "children of node {:?} are out of order", key)
+            }
+            AvlError::HeightMismatch {
+                key,
+                stored,
+                actual,
+            } => write!(
+                f,
+                "node {:?} stores height {} but its actual height is {}",
+                key, stored, actual
+            ),
+            AvlError::SizeMismatch {
+                key,
+                stored,
+                actual,
+            } => write!(
+                f,
+                "node {:?} stores size {} but its subtree has {} nodes",
+                key, stored, actual
+            ),
+            AvlError::BalanceViolation { key, balance } => {
+                write!(f, "node {:?} has balance factor {}", key, balance)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Debug> std::error::Error for AvlError<K> {}
@@ -1,70 +1,121 @@
-use crate::AVLTree;
-use std::collections::{Bound, VecDeque};
+use crate::compare::Comparator;
+use crate::node::{Link, Node};
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::iter::Peekable;
+use core::ops::Bound;
 
 // 范围迭代器
-pub struct RangePairIter<'a, K: PartialOrd + Clone, V> {
-    tree: &'a AVLTree<K, V>, // AVL树的借用
+// 正反两个方向各使用一个显式栈，构造时从根节点下降到边界处，之后每一步均摊O(1)
+pub struct RangePairIter<'a, K: Ord + Clone, V> {
     from: Bound<K>, // 范围的起点
     to: Bound<K>, //范围的终点
+    front: Vec<&'a Node<K, V>>, // 正向迭代的栈，栈顶为下一个输出的节点
+    back: Vec<&'a Node<K, V>>, // 反向迭代的栈，栈顶为下一个输出的节点
     prev: Option<&'a K>, // 前一次迭代时输出的key
+    back_prev: Option<&'a K>, // 前一次反向迭代时输出的key
+    cmp: &'a Comparator<K>, // 树的比较规则
 }
 
-impl<'a, K: PartialOrd + Clone, V> RangePairIter<'a, K, V> {
-    pub fn new(tree: &'a AVLTree<K, V>, lower: Bound<K>, upper: Bound<K>) -> Self {
-        Self {
-            tree,
+impl<'a, K: Ord + Clone, V> RangePairIter<'a, K, V> {
+    pub fn new(
+        root: &'a Link<K, V>,
+        lower: Bound<K>,
+        upper: Bound<K>,
+        cmp: &'a Comparator<K>,
+    ) -> Self {
+        let mut iter = Self {
             from: lower,
             to: upper,
+            front: Vec::new(),
+            back: Vec::new(),
             prev: None,
-        }
+            back_prev: None,
+            cmp,
+        };
+        iter.seek_lower_bound(root);
+        iter.seek_upper_bound(root);
+        iter
     }
 
-    // 获取迭代器中的下一个键值对，检查上下边界
-    fn get_next_key_under(&mut self) -> Option<(&'a K, &'a V)> {
-        let res = self
-            .get_next_pair()
-            .and_then(|cur| self.check_upper_bound(cur));
-        if let Some((key, _)) = res {
-            self.prev = Some(key);
+    // 从根节点下降到下边界，将路径上满足下边界的节点压栈，栈顶即为第一个满足下边界的节点
+    fn seek_lower_bound(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            if self.above_lower_bound(node.pair().0) {
+                self.front.push(node);
+                link = node.left();
+            } else {
+                link = node.right();
+            }
         }
-        res
     }
 
-    // 获取迭代器中的下一个键值对，检查下边界
-    fn get_next_pair(&mut self) -> Option<(&'a K, &'a V)> {
-        match self.prev {
-            None => self.get_lower_bound_pair(),
-            Some(key) => self.tree.successor(key),
+    // 从根节点下降到上边界，将路径上满足上边界的节点压栈，栈顶即为最后一个满足上边界的节点
+    fn seek_upper_bound(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            if self.below_upper_bound(node.pair().0) {
+                self.back.push(node);
+                link = node.right();
+            } else {
+                link = node.left();
+            }
         }
     }
 
-    // 获取下边界对应的键值对
-    fn get_lower_bound_pair(&self) -> Option<(&'a K, &'a V)> {
-        match self.from {
-            Bound::Included(ref key) => {
-                self.tree.get_pair(key).or_else(|| self.tree.successor(key))
-            }
-            Bound::Excluded(ref key) => self.tree.successor(key),
-            Bound::Unbounded => self.tree.min_pair(),
+    // 检查是否满足下边界
+    fn above_lower_bound(&self, key: &K) -> bool {
+        self.cmp.above_lower_bound(key, self.from.as_ref())
+    }
+
+    // 检查是否满足上边界
+    fn below_upper_bound(&self, key: &K) -> bool {
+        self.cmp.below_upper_bound(key, self.to.as_ref())
+    }
+
+    // 获取迭代器中的下一个键值对，检查上边界，并且不越过反向迭代已输出的键
+    fn get_next_key_under(&mut self) -> Option<(&'a K, &'a V)> {
+        let node = self.front.pop()?;
+        let mut link = node.right();
+        while let Some(next) = link {
+            self.front.push(next);
+            link = next.left();
+        }
+        let (key, value) = node.pair();
+        let crossed = self
+            .back_prev
+            .is_some_and(|back| self.cmp.compare(key, back) != Ordering::Less);
+        if !self.below_upper_bound(key) || crossed {
+            self.front.clear();
+            return None;
         }
+        self.prev = Some(key);
+        Some((key, value))
     }
 
-    // 检查是否超过上边界，超过则返回None
-    fn check_upper_bound(&self, current: (&'a K, &'a V)) -> Option<(&'a K, &'a V)> {
-        let ok = match self.to {
-            Bound::Included(ref key) => current.0 <= key,
-            Bound::Excluded(ref key) => current.0 < key,
-            Bound::Unbounded => true,
-        };
-        if ok {
-            Some(current)
-        } else {
-            None
+    // 反向获取迭代器中的下一个键值对，检查下边界，并且不越过正向迭代已输出的键
+    fn get_next_back_key_above(&mut self) -> Option<(&'a K, &'a V)> {
+        let node = self.back.pop()?;
+        let mut link = node.left();
+        while let Some(next) = link {
+            self.back.push(next);
+            link = next.right();
         }
+        let (key, value) = node.pair();
+        let crossed = self
+            .prev
+            .is_some_and(|prev| self.cmp.compare(key, prev) != Ordering::Greater);
+        if !self.above_lower_bound(key) || crossed {
+            self.back.clear();
+            return None;
+        }
+        self.back_prev = Some(key);
+        Some((key, value))
     }
 }
 
-impl<'a, K: PartialOrd + Clone, V> Iterator for RangePairIter<'a, K, V> {
+impl<'a, K: Ord + Clone, V> Iterator for RangePairIter<'a, K, V> {
     type Item = (&'a K, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -72,21 +123,544 @@ impl<'a, K: PartialOrd + Clone, V> Iterator for RangePairIter<'a, K, V> {
     }
 }
 
+impl<'a, K: Ord + Clone, V> DoubleEndedIterator for RangePairIter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.get_next_back_key_above()
+    }
+}
+
+// 按键的降序输出范围内键值对的迭代器
+// 构造时沿上边界压入右侧路径，之后按前驱的顺序输出，直到越过下边界
+pub struct RevRangeIter<'a, K: Ord + Clone, V> {
+    inner: RangePairIter<'a, K, V>,
+}
+
+impl<'a, K: Ord + Clone, V> RevRangeIter<'a, K, V> {
+    pub fn new(inner: RangePairIter<'a, K, V>) -> Self {
+        RevRangeIter { inner }
+    }
+}
+
+impl<'a, K: Ord + Clone, V> Iterator for RevRangeIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a, K: Ord + Clone, V> DoubleEndedIterator for RevRangeIter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+//遍历的顺序
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TraverseOrder {
+    Pre,
+    In,
+    Post,
+    Level,
+}
+
 //遍历迭代器，包括前序、中序、后序、层序
+//按需访问节点：前序、中序、后序把nodes当作栈使用，层序把nodes当作队列使用，每一步均摊O(1)
 pub struct TraverseIter<'a, K, V> {
-    data: VecDeque<(&'a K, &'a V)>,
+    order: TraverseOrder,
+    nodes: VecDeque<&'a Node<K, V>>,
+    remaining: usize, // 尚未输出的节点个数，由子树大小得到
 }
 
 impl<'a, K, V> TraverseIter<'a, K, V> {
-    pub fn new(queue: VecDeque<(&'a K, &'a V)>) -> Self {
-        TraverseIter { data: queue }
+    pub fn new(root: &'a Link<K, V>, order: TraverseOrder) -> Self {
+        let mut iter = TraverseIter {
+            order,
+            nodes: VecDeque::new(),
+            remaining: Node::size(root) as usize,
+        };
+        match order {
+            TraverseOrder::Pre | TraverseOrder::Level => iter.nodes.extend(root.as_deref()),
+            TraverseOrder::In => iter.push_left_spine(root.as_deref()),
+            TraverseOrder::Post => iter.push_first_leaf(root.as_deref()),
+        }
+        iter
+    }
+
+    // 将节点及其左侧路径依次压入栈中
+    fn push_left_spine(&mut self, mut node: Option<&'a Node<K, V>>) {
+        while let Some(n) = node {
+            self.nodes.push_back(n);
+            node = n.left().as_deref();
+        }
+    }
+
+    // 从节点出发优先向左、没有左孩子时向右下降到叶子，沿途节点依次压入栈中，栈顶为后序遍历的第一个节点
+    fn push_first_leaf(&mut self, mut node: Option<&'a Node<K, V>>) {
+        while let Some(n) = node {
+            self.nodes.push_back(n);
+            node = n.left().as_deref().or(n.right().as_deref());
+        }
     }
 }
 
-impl<'a, K: PartialOrd + Clone, V> Iterator for TraverseIter<'a, K, V> {
+impl<'a, K, V> Iterator for TraverseIter<'a, K, V> {
     type Item = (&'a K, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.data.pop_front()
+        let node = match self.order {
+            TraverseOrder::Pre => {
+                let node = self.nodes.pop_back()?;
+                self.nodes.extend(node.right().as_deref());
+                self.nodes.extend(node.left().as_deref());
+                node
+            }
+            TraverseOrder::In => {
+                let node = self.nodes.pop_back()?;
+                self.push_left_spine(node.right().as_deref());
+                node
+            }
+            TraverseOrder::Post => {
+                let node = self.nodes.pop_back()?;
+                // 刚输出的是父节点的左孩子时，下一步转到父节点的右子树
+                if let Some(&parent) = self.nodes.back() {
+                    let from_left = parent
+                        .left()
+                        .as_deref()
+                        .is_some_and(|left| core::ptr::eq(left, node));
+                    if from_left {
+                        self.push_first_leaf(parent.right().as_deref());
+                    }
+                }
+                node
+            }
+            TraverseOrder::Level => {
+                let node = self.nodes.pop_front()?;
+                self.nodes.extend(node.left().as_deref());
+                self.nodes.extend(node.right().as_deref());
+                node
+            }
+        };
+        self.remaining -= 1;
+        Some(node.pair())
+    }
+
+    // 剩余的节点个数在构造时由子树大小得到，每输出一个减一
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for TraverseIter<'a, K, V> {
+    fn len(&self) -> usize {
+        self.remaining
+    }
+}
+
+// 消耗AVL树的中序迭代器，按键的升序输出拥有所有权的键值对
+pub struct IntoIter<K, V> {
+    stack: Vec<Box<Node<K, V>>>, // 待访问节点的栈，栈顶为下一个输出的节点
+}
+
+impl<K, V> IntoIter<K, V> {
+    pub fn new(root: Link<K, V>) -> Self {
+        let mut iter = IntoIter { stack: Vec::new() };
+        iter.push_left_spine(root);
+        iter
+    }
+
+    // 将子树的左侧路径依次压入栈中，压栈时取走左子树，避免递归
+    fn push_left_spine(&mut self, mut link: Link<K, V>) {
+        while let Some(mut node) = link {
+            link = node.take_left();
+            self.stack.push(node);
+        }
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?;
+        let right = node.take_right();
+        self.push_left_spine(right);
+        Some(node.into_pair())
+    }
+}
+
+// 消耗AVL树，按键的升序输出拥有所有权的键
+pub struct IntoKeys<K, V> {
+    inner: IntoIter<K, V>,
+}
+
+impl<K, V> IntoKeys<K, V> {
+    pub fn new(inner: IntoIter<K, V>) -> Self {
+        IntoKeys { inner }
+    }
+}
+
+impl<K, V> Iterator for IntoKeys<K, V> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+}
+
+// 消耗AVL树，按键的升序输出拥有所有权的值
+pub struct IntoValues<K, V> {
+    inner: IntoIter<K, V>,
+}
+
+impl<K, V> IntoValues<K, V> {
+    pub fn new(inner: IntoIter<K, V>) -> Self {
+        IntoValues { inner }
+    }
+}
+
+impl<K, V> Iterator for IntoValues<K, V> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+}
+
+// 中序遍历的惰性迭代器，使用显式栈保存待访问的节点，空间复杂度O(h)
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    pub fn new(root: &'a Link<K, V>) -> Self {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left_spine(root);
+        iter
+    }
+
+    // 将子树的左侧路径依次压入栈中
+    fn push_left_spine(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = node.left();
+        }
+    }
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    // 按子树大小从根节点下降到排名为rank(从0开始)的节点，栈顶即为该节点，之后的遍历与new相同
+    pub fn from_rank(root: &'a Link<K, V>, mut rank: usize) -> Self {
+        let mut iter = Iter { stack: Vec::new() };
+        let mut link = root;
+        while let Some(node) = link {
+            let left_size = Node::size(node.left()) as usize;
+            if rank < left_size {
+                iter.stack.push(node);
+                link = node.left();
+            } else if rank == left_size {
+                iter.stack.push(node);
+                break;
+            } else {
+                rank -= left_size + 1;
+                link = node.right();
+            }
+        }
+        iter
+    }
+}
+
+impl<'a, K: Ord, V> Iter<'a, K, V> {
+    // 从根节点下降到key，只将键大于等于key的节点压栈，栈顶即为第一个大于等于key的节点
+    pub fn from_key(root: &'a Link<K, V>, key: &K, cmp: &Comparator<K>) -> Self {
+        let mut iter = Iter { stack: Vec::new() };
+        let mut link = root;
+        while let Some(node) = link {
+            if cmp.compare(node.pair().0, key) == Ordering::Less {
+                link = node.right();
+            } else {
+                iter.stack.push(node);
+                link = node.left();
+            }
+        }
+        iter
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(node.right());
+        Some(node.pair())
+    }
+}
+
+// 反向中序遍历的惰性迭代器，按键的降序输出，与Iter对称地先访问右子树
+pub struct RevIter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> RevIter<'a, K, V> {
+    pub fn new(root: &'a Link<K, V>) -> Self {
+        let mut iter = RevIter { stack: Vec::new() };
+        iter.push_right_spine(root);
+        iter
+    }
+
+    // 将子树的右侧路径依次压入栈中
+    fn push_right_spine(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = node.right();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for RevIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_right_spine(node.left());
+        Some(node.pair())
+    }
+}
+
+// 按键的升序输出键的迭代器
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Keys<'a, K, V> {
+    pub fn new(inner: Iter<'a, K, V>) -> Self {
+        Keys { inner }
+    }
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+}
+
+// 按键的升序输出值的迭代器
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Values<'a, K, V> {
+    pub fn new(inner: Iter<'a, K, V>) -> Self {
+        Values { inner }
+    }
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+}
+
+// 按键的升序输出值的可变借用的迭代器
+// 栈中保存的是已拆分节点的值和右子树，每个节点的值只会被借出一次，不会产生别名
+pub struct ValuesMut<'a, K, V> {
+    stack: Vec<(&'a mut V, &'a mut Link<K, V>)>,
+}
+
+impl<'a, K, V> ValuesMut<'a, K, V> {
+    pub fn new(root: &'a mut Link<K, V>) -> Self {
+        let mut iter = ValuesMut { stack: Vec::new() };
+        iter.push_left_spine(root);
+        iter
+    }
+
+    // 将子树的左侧路径依次拆分并压入栈中
+    fn push_left_spine(&mut self, mut link: &'a mut Link<K, V>) {
+        while let Some(node) = link {
+            let (_, value, left, right) = node.split_mut();
+            self.stack.push((value, right));
+            link = left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (value, right) = self.stack.pop()?;
+        self.push_left_spine(right);
+        Some(value)
+    }
+}
+
+// 可变范围迭代器，按键的升序输出范围内的键和值的可变借用
+// 栈中保存已拆分的节点，每个节点的值和右子树只会被拆出一次，因此输出的可变借用互不重叠
+pub struct RangeMut<'a, K, V> {
+    to: Bound<K>, // 范围的终点
+    stack: Vec<(&'a K, &'a mut V, &'a mut Link<K, V>)>, // 栈顶为下一个输出的节点
+    cmp: &'a Comparator<K>, // 树的比较规则
+}
+
+impl<'a, K: Ord, V> RangeMut<'a, K, V> {
+    pub fn new(
+        root: &'a mut Link<K, V>,
+        lower: Bound<K>,
+        upper: Bound<K>,
+        cmp: &'a Comparator<K>,
+    ) -> Self {
+        let mut iter = RangeMut {
+            to: upper,
+            stack: Vec::new(),
+            cmp,
+        };
+        // 从根节点下降到下边界，只将满足下边界的节点压栈
+        let mut link = root;
+        while let Some(node) = link {
+            let (key, value, left, right) = node.split_mut();
+            if cmp.above_lower_bound(key, lower.as_ref()) {
+                iter.stack.push((key, value, right));
+                link = left;
+            } else {
+                link = right;
+            }
+        }
+        iter
+    }
+
+    // 将子树的左侧路径依次拆分并压入栈中
+    fn push_left_spine(&mut self, mut link: &'a mut Link<K, V>) {
+        while let Some(node) = link {
+            let (key, value, left, right) = node.split_mut();
+            self.stack.push((key, value, right));
+            link = left;
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for RangeMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value, right) = self.stack.pop()?;
+        if !self.cmp.below_upper_bound(key, self.to.as_ref()) {
+            self.stack.clear();
+            return None;
+        }
+        self.push_left_spine(right);
+        Some((key, value))
+    }
+}
+
+// 惰性合并两棵AVL树的中序序列，键相同时输出左侧(self)的键值对并跳过右侧的
+pub struct MergeIter<'a, K, V> {
+    left: Peekable<Iter<'a, K, V>>,
+    right: Peekable<Iter<'a, K, V>>,
+    cmp: &'a Comparator<K>, // 左侧(self)的比较规则
+}
+
+impl<'a, K, V> MergeIter<'a, K, V> {
+    pub fn new(left: Iter<'a, K, V>, right: Iter<'a, K, V>, cmp: &'a Comparator<K>) -> Self {
+        MergeIter {
+            left: left.peekable(),
+            right: right.peekable(),
+            cmp,
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for MergeIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match (self.left.peek(), self.right.peek()) {
+            (Some((l, _)), Some((r, _))) => match self.cmp.compare(l, r) {
+                Ordering::Less => self.left.next(),
+                Ordering::Greater => self.right.next(),
+                Ordering::Equal => {
+                    self.right.next();
+                    self.left.next()
+                }
+            },
+            (Some(_), None) => self.left.next(),
+            (None, _) => self.right.next(),
+        }
+    }
+}
+
+// 两棵树键集合之间的运算
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SetOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+// 惰性合并两棵AVL树的有序键序列，按集合运算决定每个键是否输出
+pub struct SetOpKeys<'a, K, V> {
+    left: Peekable<Keys<'a, K, V>>,
+    right: Peekable<Keys<'a, K, V>>,
+    op: SetOperation,
+    cmp: &'a Comparator<K>, // 左侧(self)的比较规则
+}
+
+impl<'a, K, V> SetOpKeys<'a, K, V> {
+    pub fn new(
+        left: Keys<'a, K, V>,
+        right: Keys<'a, K, V>,
+        op: SetOperation,
+        cmp: &'a Comparator<K>,
+    ) -> Self {
+        SetOpKeys {
+            left: left.peekable(),
+            right: right.peekable(),
+            op,
+            cmp,
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for SetOpKeys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match (self.left.peek(), self.right.peek()) {
+                (Some(l), Some(r)) => match self.cmp.compare(l, r) {
+                    // 只在左侧出现的键
+                    Ordering::Less => {
+                        let key = self.left.next();
+                        if self.op != SetOperation::Intersection {
+                            return key;
+                        }
+                    }
+                    // 只在右侧出现的键
+                    Ordering::Greater => {
+                        let key = self.right.next();
+                        if self.op == SetOperation::Union {
+                            return key;
+                        }
+                    }
+                    // 两侧都出现的键，输出左侧的
+                    Ordering::Equal => {
+                        self.right.next();
+                        let key = self.left.next();
+                        if self.op != SetOperation::Difference {
+                            return key;
+                        }
+                    }
+                },
+                (Some(_), None) if self.op != SetOperation::Intersection => {
+                    return self.left.next()
+                }
+                (None, Some(_)) if self.op == SetOperation::Union => return self.right.next(),
+                _ => return None,
+            }
+        }
     }
 }
@@ -1,5 +1,24 @@
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 mod node;
 mod iterator;
 
+mod arena;
 mod avltree;
+mod compare;
+mod entry;
+mod error;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod snapshot;
+mod sum_tree;
+pub use arena::CompactTree;
 pub use avltree::AVLTree;
+pub use entry::{Entry, OccupiedEntry, OccupiedError, TryInsertError, VacantEntry};
+pub use error::AvlError;
+pub use node::{RotationKind, RotationStep};
+pub use sum_tree::SumTree;
@@ -1,69 +1,315 @@
-use std::cmp::max;
-use std::collections::VecDeque;
+use crate::compare::Comparator;
+use crate::error::AvlError;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::{max, Ordering};
+use core::ops::{Add, Bound, RangeBounds};
+
+pub type Link<K, V, A = ()> = Option<Box<Node<K, V, A>>>;
+
+/// 旋转的类型，以失衡节点到插入位置的路径命名
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationKind {
+    /// 左子树的左侧过高，对失衡节点做一次右旋
+    LL,
+    /// 右子树的右侧过高，对失衡节点做一次左旋
+    RR,
+    /// 左子树的右侧过高，先左旋左子树再右旋失衡节点
+    LR,
+    /// 右子树的左侧过高，先右旋右子树再左旋失衡节点
+    RL,
+}
+
+/// 一次平衡调整的记录，包括失衡节点的键和旋转类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationStep<K> {
+    pub pivot: K,
+    pub kind: RotationKind,
+}
+
+// 记录旋转过程的缓冲区，为None时不记录
+type Trace<'a, K> = Option<&'a mut Vec<RotationStep<K>>>;
+
+// 节点中维护的子树聚合值，由左右子树的聚合值和节点自身的值计算，与高度、子树大小一起在结构改变时更新
+// AVLTree使用()，不占用空间也不做任何计算
+pub trait Aggregate<V> {
+    fn combine(left: Option<&Self>, value: &V, right: Option<&Self>) -> Self;
+}
 
-pub type Link<K, V> = Option<Box<Node<K, V>>>;
+impl<V> Aggregate<V> for () {
+    fn combine(_: Option<&()>, _: &V, _: Option<&()>) {}
+}
+
+// 子树所有值的和，SumTree使用
+#[derive(Clone)]
+pub struct Sum<V>(V);
+
+impl<V: Add<Output = V> + Clone> Aggregate<V> for Sum<V> {
+    // 按左子树、节点、右子树的顺序相加，不要求加法满足交换律
+    fn combine(left: Option<&Self>, value: &V, right: Option<&Self>) -> Self {
+        let sum = match left {
+            Some(Sum(left)) => left.clone() + value.clone(),
+            None => value.clone(),
+        };
+        match right {
+            Some(Sum(right)) => Sum(sum + right.clone()),
+            None => Sum(sum),
+        }
+    }
+}
 
-pub struct Node<K, V> {
+#[derive(Clone)]
+pub struct Node<K, V, A = ()> {
     key: K, //键
     value: V, //值
-    height: u32, //树高
-    left: Link<K, V>,
-    right: Link<K, V>,
+    height: u16, //树高，AVL树的高度不超过约1.44*log2(n)，u16已足够
+    size: u32, //子树的节点个数
+    agg: A, //子树的聚合值
+    left: Link<K, V, A>,
+    right: Link<K, V, A>,
+}
+
+impl<K, V, A> Node<K, V, A> {
+    // 得到以当前节点为根的子树的节点个数
+    pub fn size(node: &Link<K, V, A>) -> u32 {
+        node.as_ref().map_or(0, |node| node.size)
+    }
+
+    // 返回左子树的不可变借用
+    pub fn left(&self) -> &Link<K, V, A> {
+        &self.left
+    }
+
+    // 返回右子树的不可变借用
+    pub fn right(&self) -> &Link<K, V, A> {
+        &self.right
+    }
+
+    // 返回当前节点的键值对
+    pub fn pair(&self) -> (&K, &V) {
+        (&self.key, &self.value)
+    }
+
+    // 取出左子树
+    pub fn take_left(&mut self) -> Link<K, V, A> {
+        self.left.take()
+    }
+
+    // 取出右子树
+    pub fn take_right(&mut self) -> Link<K, V, A> {
+        self.right.take()
+    }
+
+    // 沿左侧路径下降，返回子树中最小的节点
+    pub fn min_node(&self) -> &Self {
+        let mut node = self;
+        while let Some(left) = &node.left {
+            node = left;
+        }
+        node
+    }
+
+    // 沿右侧路径下降，返回子树中最大的节点
+    pub fn max_node(&self) -> &Self {
+        let mut node = self;
+        while let Some(right) = &node.right {
+            node = right;
+        }
+        node
+    }
+
+    // 消耗节点，返回其中的键值对
+    pub fn into_pair(self) -> (K, V) {
+        (self.key, self.value)
+    }
+}
+
+impl<K, V> Node<K, V> {
+    // 将节点拆分为互不重叠的可变借用：(键, 值, 左子树, 右子树)
+    // 只有不维护聚合值的节点可以交出值的可变借用，否则修改值之后聚合值会失效
+    pub fn split_mut(&mut self) -> (&K, &mut V, &mut Link<K, V>, &mut Link<K, V>) {
+        (&self.key, &mut self.value, &mut self.left, &mut self.right)
+    }
+
+    // 保持树的结构不变，将每个节点的值替换为f的结果，高度和子树大小直接沿用
+    pub fn map_values<W, F: FnMut(&V) -> W>(self, f: &mut F) -> Node<K, W> {
+        let left = self.left.map(|left| Box::new(left.map_values(f)));
+        let value = f(&self.value);
+        let right = self.right.map(|right| Box::new(right.map_values(f)));
+        Node {
+            key: self.key,
+            value,
+            height: self.height,
+            size: self.size,
+            agg: (),
+            left,
+            right,
+        }
+    }
+}
+
+impl<K: Clone, V: Clone> Node<K, V> {
+    // 将src的结构和内容复制到dst中，两边都存在的节点直接原地覆盖，复用已有的内存
+    pub fn clone_link_from(dst: &mut Link<K, V>, src: &Link<K, V>) {
+        match (dst.as_mut(), src) {
+            (_, None) => *dst = None,
+            (None, Some(src)) => *dst = Some(src.clone()),
+            (Some(node), Some(src)) => {
+                node.key.clone_from(&src.key);
+                node.value.clone_from(&src.value);
+                node.height = src.height;
+                node.size = src.size;
+                Node::clone_link_from(&mut node.left, &src.left);
+                Node::clone_link_from(&mut node.right, &src.right);
+            }
+        }
+    }
+}
+
+impl<K: Ord, V> Node<K, V> {
+    // 返回第一个大于等于key的键值对,key可以不存在树中
+    pub fn ceiling(&self, key: &K, cmp: &Comparator<K>) -> Option<(&K, &V)> {
+        match cmp.compare(&self.key, key) {
+            Ordering::Less => self
+                .right
+                .as_ref()
+                .and_then(|right| right.ceiling(key, cmp)),
+            Ordering::Greater => self
+                .left
+                .as_ref()
+                .and_then(|left| left.ceiling(key, cmp))
+                .or(Some((&self.key, &self.value))),
+            Ordering::Equal => Some((&self.key, &self.value)),
+        }
+    }
+
+    // 返回最后一个小于等于key的键值对,key可以不存在树中
+    pub fn floor(&self, key: &K, cmp: &Comparator<K>) -> Option<(&K, &V)> {
+        match cmp.compare(&self.key, key) {
+            Ordering::Greater => self.left.as_ref().and_then(|left| left.floor(key, cmp)),
+            Ordering::Less => self
+                .right
+                .as_ref()
+                .and_then(|right| right.floor(key, cmp))
+                .or(Some((&self.key, &self.value))),
+            Ordering::Equal => Some((&self.key, &self.value)),
+        }
+    }
 }
 
-impl<K: PartialOrd + Clone, V> Node<K, V> {
+impl<K: Ord + Clone, V, A: Aggregate<V>> Node<K, V, A> {
     pub fn new(key: K, value: V) -> Self {
         Node {
+            agg: A::combine(None, &value, None),
             key,
             value,
             height: 1,
+            size: 1,
             left: None,
             right: None,
         }
     }
 
+    // 由键值对和左右子树构造节点，并计算节点高度、子树大小和聚合值
+    pub fn with_children(key: K, value: V, left: Link<K, V, A>, right: Link<K, V, A>) -> Self {
+        let mut node = Node {
+            agg: A::combine(None, &value, None),
+            key,
+            value,
+            height: 1,
+            size: 1,
+            left,
+            right,
+        };
+        node.update_metadata();
+        node
+    }
+
+    // 由按键升序排列的n个键值对构建一棵平衡的树，返回树的根节点
+    pub fn from_sorted<I: Iterator<Item = (K, V)>>(pairs: &mut I, n: usize) -> Link<K, V, A> {
+        if n == 0 {
+            return None;
+        }
+        let left = Self::from_sorted(pairs, n / 2);
+        let (key, value) = pairs.next().expect("not enough sorted pairs");
+        let right = Self::from_sorted(pairs, n - n / 2 - 1);
+        Some(Box::new(Node::with_children(key, value, left, right)))
+    }
+
     // 判断当前节点是否为叶子节点
     fn is_leaf(&self) -> bool {
         self.left.is_none() && self.right.is_none()
     }
 
     // 得到当前节点的高度
-    fn height(node: &Link<K, V>) -> u32 {
-        node.as_ref().map_or(0, |node| node.height)
+    pub fn height(node: &Link<K, V, A>) -> u32 {
+        node.as_ref().map_or(0, |node| u32::from(node.height))
+    }
+
+    // 判断两棵子树的形状是否相同：每个位置上的键和高度都相等，不比较值
+    pub fn same_shape<W>(node: &Link<K, V, A>, other: &Link<K, W, A>) -> bool {
+        match (node, other) {
+            (None, None) => true,
+            (Some(node), Some(other)) => {
+                node.height == other.height
+                    && node.key == other.key
+                    && Node::same_shape(&node.left, &other.left)
+                    && Node::same_shape(&node.right, &other.right)
+            }
+            _ => false,
+        }
     }
 
-    // 更新当前节点的高度
-    fn update_height(&mut self) {
-        self.height = max(Self::height(&self.left), Self::height(&self.right)) + 1;
+    // 由左右子树更新当前节点的高度、子树大小和聚合值
+    fn update_metadata(&mut self) {
+        self.height = (max(Self::height(&self.left), Self::height(&self.right)) + 1) as u16;
+        self.size = Self::size(&self.left) + Self::size(&self.right) + 1;
+        let left = self.left.as_ref().map(|left| &left.agg);
+        let right = self.right.as_ref().map(|right| &right.agg);
+        self.agg = A::combine(left, &self.value, right);
     }
 
     //对当前节点进行一次左旋操作，返回旋转后的根节点
-    fn left_rotate(mut self) -> Box<Node<K, V>> {
+    fn left_rotate(mut self) -> Box<Self> {
         let mut new_root = self.right.take().expect("AVL broken");
         self.right = new_root.left.take();
-        self.update_height();
+        self.update_metadata();
         new_root.left = Some(Box::new(self));
-        new_root.update_height();
+        new_root.update_metadata();
         new_root
     }
 
     //对当前节点进行一次右旋操作，返回旋转后的根节点
-    fn right_rotate(mut self) -> Box<Node<K, V>> {
+    fn right_rotate(mut self) -> Box<Self> {
         let mut new_root = self.left.take().expect("AVL broken");
         self.left = new_root.right.take();
-        self.update_height();
+        self.update_metadata();
         new_root.right = Some(Box::new(self));
-        new_root.update_height();
+        new_root.update_metadata();
         new_root
     }
 
     //保持左侧平衡。传入的self是一颗不平衡的树，左子树比右子树高2
-    fn left_balance(mut self) -> Box<Node<K, V>> {
+    fn left_balance(mut self, trace: Trace<K>) -> Box<Self> {
         let left = self.left.take().expect("AVL broken");
-        if Self::height(&left.left) < Self::height(&left.right) {
+        let double = Self::height(&left.left) < Self::height(&left.right);
+        if let Some(trace) = trace {
+            let kind = if double {
+                RotationKind::LR
+            } else {
+                RotationKind::LL
+            };
+            trace.push(RotationStep {
+                pivot: self.key.clone(),
+                kind,
+            });
+        }
+        if double {
             let rotated = left.left_rotate();
             self.left = Some(rotated);
-            self.update_height();
+            self.update_metadata();
         } else {
             self.left = Some(left);
         }
@@ -71,12 +317,24 @@ impl<K: PartialOrd + Clone, V> Node<K, V> {
     }
 
     //保持右侧平衡。传入的self是一颗不平衡的树，右子树比左子树高2
-    fn right_balance(mut self) -> Box<Node<K, V>> {
+    fn right_balance(mut self, trace: Trace<K>) -> Box<Self> {
         let right = self.right.take().expect("AVL broken");
-        if Self::height(&right.left) > Self::height(&right.right) {
+        let double = Self::height(&right.left) > Self::height(&right.right);
+        if let Some(trace) = trace {
+            let kind = if double {
+                RotationKind::RL
+            } else {
+                RotationKind::RR
+            };
+            trace.push(RotationStep {
+                pivot: self.key.clone(),
+                kind,
+            });
+        }
+        if double {
             let rotated = right.right_rotate();
             self.right = Some(rotated);
-            self.update_height();
+            self.update_metadata();
         } else {
             self.right = Some(right);
         }
@@ -91,54 +349,77 @@ impl<K: PartialOrd + Clone, V> Node<K, V> {
     }
 
     //判断当前节点是否需要进行旋转调整，返回调整后的根节点
-    fn rotate_if_necessary(self) -> Box<Node<K, V>> {
+    fn rotate_if_necessary(self, trace: Trace<K>) -> Box<Self> {
         let diff = self.diff_of_height();
-        if -1 <= diff && diff <= 1 {
+        if (-1..=1).contains(&diff) {
             Box::new(self)
         } else if diff == -2 {
-            self.right_balance()
+            self.right_balance(trace)
         } else if diff == 2 {
-            self.left_balance()
+            self.left_balance(trace)
         } else {
             unreachable!()
         }
     }
 
     //更新当前根节点，包括高度更新和旋转操作
-    fn update_node(mut self) -> Box<Node<K, V>> {
-        self.update_height();
-        self.rotate_if_necessary()
+    fn update_node(self) -> Box<Self> {
+        self.update_node_traced(None)
+    }
+
+    //更新当前根节点，并将发生的旋转记录到trace中
+    fn update_node_traced(mut self, trace: Trace<K>) -> Box<Self> {
+        self.update_metadata();
+        self.rotate_if_necessary(trace)
     }
 
     //插入新节点，并返回调整后的根节点
-    pub fn insert(mut self, key: K, value: V) -> Box<Node<K, V>> {
-        if self.key > key {
-            match self.left.take() {
-                None => {
-                    self.left = Some(Box::new(Node::new(key, value)));
-                }
-                Some(node) => {
-                    self.left = Some(node.insert(key, value));
+    pub fn insert(self, key: K, value: V, cmp: &Comparator<K>) -> Box<Self> {
+        self.insert_traced(key, value, None, cmp)
+    }
+
+    //插入新节点，将插入过程中发生的旋转记录到trace中，并返回调整后的根节点
+    pub fn insert_traced(
+        self,
+        key: K,
+        value: V,
+        mut trace: Trace<K>,
+        cmp: &Comparator<K>,
+    ) -> Box<Self> {
+        // 自顶向下查找插入位置，将经过的节点及下降的方向压入栈中，代替递归
+        let mut path: Vec<(Box<Self>, Ordering)> = Vec::new();
+        let mut cur = Box::new(self);
+        let mut child = loop {
+            let ordering = cmp.compare(&cur.key, &key);
+            let next = match ordering {
+                Ordering::Greater => cur.left.take(),
+                Ordering::Less => cur.right.take(),
+                Ordering::Equal => {
+                    cur.value = value;
+                    cur.update_metadata();
+                    break cur;
                 }
+            };
+            path.push((cur, ordering));
+            match next {
+                Some(node) => cur = node,
+                None => break Box::new(Node::new(key, value)),
             }
-        } else if self.key < key {
-            match self.right.take() {
-                None => {
-                    self.right = Some(Box::new(Node::new(key, value)));
-                }
-                Some(node) => {
-                    self.right = Some(node.insert(key, value));
-                }
+        };
+        // 自底向上将子树接回父节点，更新高度并在失衡时旋转
+        while let Some((mut parent, ordering)) = path.pop() {
+            if ordering == Ordering::Greater {
+                parent.left = Some(child);
+            } else {
+                parent.right = Some(child);
             }
-        } else {
-            self.value = value;
-            return Box::new(self);
+            child = parent.update_node_traced(trace.as_deref_mut());
         }
-        self.update_node()
+        child
     }
 
     //找出当前树中值最小的节点，返回元组:(除去最小节点后剩下的树，最小节点)
-    fn remove_min(mut self) -> (Link<K, V>, Box<Node<K, V>>) {
+    pub fn remove_min(mut self) -> (Link<K, V, A>, Box<Self>) {
         match self.left.take() {
             Some(left) => {
                 let (new_left, min) = left.remove_min();
@@ -149,11 +430,23 @@ impl<K: PartialOrd + Clone, V> Node<K, V> {
         }
     }
 
+    //找出当前树中值最大的节点，返回元组:(除去最大节点后剩下的树，最大节点)
+    pub fn remove_max(mut self) -> (Link<K, V, A>, Box<Self>) {
+        match self.right.take() {
+            Some(right) => {
+                let (new_right, max) = right.remove_max();
+                self.right = new_right;
+                (Some(self.update_node()), max)
+            }
+            None => (self.left.take(), Box::new(self)),
+        }
+    }
+
     //将两棵子树合并为一棵，合并后仍然满足AVL树的规则，返回新生成树的根节点
     fn combine_two_subtrees(
-        left: Node<K, V>,
-        right: Node<K, V>,
-    ) -> Box<Node<K, V>> {
+        left: Node<K, V, A>,
+        right: Node<K, V, A>,
+    ) -> Box<Self> {
         // 得到右子树中最小的节点和去除最小节点后剩余的树
         let (remain_tree, min) = right.remove_min();
         // 最小节点作为两个子树的新根节点
@@ -164,7 +457,7 @@ impl<K: PartialOrd + Clone, V> Node<K, V> {
     }
 
     //删除当前节点，重构二叉树，并返回新的根节点
-    fn delete_root(mut self) -> Link<K, V> {
+    fn delete_root(mut self) -> Link<K, V, A> {
         // AVL树删除节点的三种情况(包括二叉搜索树)，AVL树的删除还要多一步旋转操作
         // 1.如果是叶子节点，则直接删除
         // 2.如果待删除节点只有左子树或只有右子树，删除该节点，然后将左子树或右子树移动到该节点
@@ -178,114 +471,182 @@ impl<K: PartialOrd + Clone, V> Node<K, V> {
     }
 
     //删除节点key，并保持改树仍为AVL树，返回的新生成的树的根节点
-    pub fn delete(mut self, key: K) -> Link<K, V> {
-        if self.key < key {
-            if let Some(succ) = self.right.take() {
-                self.right = succ.delete(key);
-                return Some(self.update_node());
+    pub fn delete(mut self, key: K, cmp: &Comparator<K>) -> Link<K, V, A> {
+        match cmp.compare(&self.key, &key) {
+            Ordering::Less => {
+                if let Some(succ) = self.right.take() {
+                    self.right = succ.delete(key, cmp);
+                    return Some(self.update_node());
+                }
             }
-        } else if self.key > key {
-            if let Some(succ) = self.left.take() {
-                self.left = succ.delete(key);
-                return Some(self.update_node());
+            Ordering::Greater => {
+                if let Some(succ) = self.left.take() {
+                    self.left = succ.delete(key, cmp);
+                    return Some(self.update_node());
+                }
             }
-        } else {
-            return self.delete_root();
+            Ordering::Equal => return self.delete_root(),
         }
         // 没有找到待删除节点则直接返回
         Some(Box::new(self))
     }
 
-    // 返回第一个大于key的键值对,key可以不存在树中
-    pub fn successor(&self, key: &K) -> Option<(&K, &V)> {
-        if self.key > *key {
-            match self.left {
-                None => Some((&self.key, &self.value)),
-                Some(ref succ) => succ.successor(key).or(Some((&self.key, &self.value))),
-            }
-        } else if self.key < *key {
-            self.right.as_ref().and_then(|right| right.successor(key))
+    //以key为分隔将两棵树连接为一棵AVL树，要求left中的键都小于key，right中的键都大于key
+    //沿较高一侧的边缘下降到与较矮一侧高度相差不超过1的位置再连接，回溯时逐层旋转，时间复杂度O(|h(left)-h(right)|+1)
+    pub fn join(left: Link<K, V, A>, key: K, value: V, right: Link<K, V, A>) -> Box<Self> {
+        let (left_height, right_height) = (Self::height(&left), Self::height(&right));
+        if left_height > right_height + 1 {
+            let mut node = left.expect("left subtree is higher");
+            let inner = node.right.take();
+            node.right = Some(Self::join(inner, key, value, right));
+            node.update_node()
+        } else if right_height > left_height + 1 {
+            let mut node = right.expect("right subtree is higher");
+            let inner = node.left.take();
+            node.left = Some(Self::join(left, key, value, inner));
+            node.update_node()
         } else {
-            self.right.as_ref().map(|right| right.min_pair())
+            Box::new(Node::with_children(key, value, left, right))
         }
     }
 
-    // 返回第一个小于key的键值对,key可以不存在树中
-    pub fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
-        if self.key < *key {
-            match self.right {
-                None => Some((&self.key, &self.value)),
-                Some(ref succ) => succ.predecessor(key).or(Some((&self.key, &self.value))),
-            }
-        } else if self.key > *key {
-            self.left.as_ref().and_then(|left| left.predecessor(key))
+    //将树按key拆分为两棵AVL树，返回元组:(键小于key的树，键大于等于key的树)，时间复杂度O(log n)
+    pub fn split(
+        root: Link<K, V, A>,
+        key: &K,
+        cmp: &Comparator<K>,
+    ) -> (Link<K, V, A>, Link<K, V, A>) {
+        let node = match root {
+            None => return (None, None),
+            Some(node) => *node,
+        };
+        let Node {
+            key: node_key,
+            value,
+            left,
+            right,
+            ..
+        } = node;
+        if cmp.compare(&node_key, key) == Ordering::Less {
+            let (less, greater) = Self::split(right, key, cmp);
+            (Some(Self::join(left, node_key, value, less)), greater)
         } else {
-            self.left.as_ref().map(|left| left.max_pair())
+            let (less, greater) = Self::split(left, key, cmp);
+            (less, Some(Self::join(greater, node_key, value, right)))
         }
     }
 
-    // 前序遍历
-    pub fn prev_order(root: &Link<K, V>, buf: &mut Vec<K>) {
-        if let Some(node) = root {
-            buf.push(node.key.clone());
-            Self::prev_order(&node.left, buf);
-            Self::prev_order(&node.right, buf);
+    // 返回第一个大于key的键值对,key可以不存在树中
+    pub fn successor(&self, key: &K, cmp: &Comparator<K>) -> Option<(&K, &V)> {
+        match cmp.compare(&self.key, key) {
+            Ordering::Greater => match self.left {
+                None => Some((&self.key, &self.value)),
+                Some(ref succ) => succ.successor(key, cmp).or(Some((&self.key, &self.value))),
+            },
+            Ordering::Less => self
+                .right
+                .as_ref()
+                .and_then(|right| right.successor(key, cmp)),
+            Ordering::Equal => self.right.as_ref().map(|right| right.min_pair()),
         }
     }
 
-    // 中序遍历
-    pub fn in_order(root: &Link<K, V>, buf: &mut Vec<K>) {
-        if let Some(node) = root {
-            Self::in_order(&node.left, buf);
-            buf.push(node.key.clone());
-            Self::in_order(&node.right, buf);
+    // 返回第一个小于key的键值对,key可以不存在树中
+    pub fn predecessor(&self, key: &K, cmp: &Comparator<K>) -> Option<(&K, &V)> {
+        match cmp.compare(&self.key, key) {
+            Ordering::Less => match self.right {
+                None => Some((&self.key, &self.value)),
+                Some(ref succ) => succ
+                    .predecessor(key, cmp)
+                    .or(Some((&self.key, &self.value))),
+            },
+            Ordering::Greater => self
+                .left
+                .as_ref()
+                .and_then(|left| left.predecessor(key, cmp)),
+            Ordering::Equal => self.left.as_ref().map(|left| left.max_pair()),
         }
     }
 
-    // 后序遍历
-    pub fn post_order(root: &Link<K, V>, buf: &mut Vec<K>) {
+    // 前序遍历，记录每个节点的键及其左右孩子的键
+    pub fn adjacency(root: &Link<K, V, A>, buf: &mut Vec<(K, Option<K>, Option<K>)>) {
         if let Some(node) = root {
-            Self::post_order(&node.left, buf);
-            Self::post_order(&node.right, buf);
-            buf.push(node.key.clone());
+            buf.push((
+                node.key.clone(),
+                node.left.as_ref().map(|left| left.key.clone()),
+                node.right.as_ref().map(|right| right.key.clone()),
+            ));
+            Self::adjacency(&node.left, buf);
+            Self::adjacency(&node.right, buf);
         }
     }
 
-    // 层序遍历
-    pub fn level_order(root: &Link<K, V>, buf: &mut Vec<K>) {
-        let mut queue = VecDeque::new();
-        if let Some(node) = root {
-            queue.push_back(node);
-        }
-        while !queue.is_empty() {
-            if let Some(node) = queue.pop_front() {
-                buf.push(node.key.clone());
-                if let Some(left) = node.left.as_ref() {
-                    queue.push_back(left);
-                }
-                if let Some(right) = node.right.as_ref() {
-                    queue.push_back(right);
-                }
+    // 逐层遍历，返回第depth层(根节点为第0层)的节点个数
+    pub fn width_at_depth(root: &Link<K, V, A>, depth: usize) -> usize {
+        let mut level: Vec<&Node<K, V, A>> = root.iter().map(|node| &**node).collect();
+        for _ in 0..depth {
+            if level.is_empty() {
+                break;
             }
+            level = level
+                .iter()
+                .flat_map(|node| node.left.iter().chain(node.right.iter()))
+                .map(|node| &**node)
+                .collect();
         }
+        level.len()
     }
 
     // 返回查找的键值对的不可变借用
-    pub fn search_pair(&self, key: &K,) -> Option<(&K, &V)> {
-        if self.key < *key {
-            self.right
+    pub fn search_pair(&self, key: &K, cmp: &Comparator<K>) -> Option<(&K, &V)> {
+        match cmp.compare(&self.key, key) {
+            Ordering::Less => self
+                .right
                 .as_ref()
-                .and_then(|right| right.search_pair(key))
-        } else if self.key > *key {
-            self.left.as_ref().and_then(|left| left.search_pair(key))
-        } else {
-            Some((&self.key, &self.value))
+                .and_then(|right| right.search_pair(key, cmp)),
+            Ordering::Greater => self
+                .left
+                .as_ref()
+                .and_then(|left| left.search_pair(key, cmp)),
+            Ordering::Equal => Some((&self.key, &self.value)),
+        }
+    }
+
+    // 返回从当前节点到键为key的节点所经过的边数
+    pub fn depth(&self, key: &K, cmp: &Comparator<K>) -> Option<usize> {
+        match cmp.compare(&self.key, key) {
+            Ordering::Less => self
+                .right
+                .as_ref()
+                .and_then(|right| right.depth(key, cmp))
+                .map(|depth| depth + 1),
+            Ordering::Greater => self
+                .left
+                .as_ref()
+                .and_then(|left| left.depth(key, cmp))
+                .map(|depth| depth + 1),
+            Ordering::Equal => Some(0),
+        }
+    }
+
+    // 返回键为key的节点的平衡因子(左子树高度减右子树高度)
+    pub fn balance_factor(&self, key: &K, cmp: &Comparator<K>) -> Option<i32> {
+        match cmp.compare(&self.key, key) {
+            Ordering::Less => self
+                .right
+                .as_ref()
+                .and_then(|right| right.balance_factor(key, cmp)),
+            Ordering::Greater => self
+                .left
+                .as_ref()
+                .and_then(|left| left.balance_factor(key, cmp)),
+            Ordering::Equal => Some(self.diff_of_height()),
         }
     }
 
     // 根据键查找对应的值
-    pub fn search(&self, key: &K) -> Option<&V> {
-        self.search_pair(key).map(|(_, v)| v)
+    pub fn search(&self, key: &K, cmp: &Comparator<K>) -> Option<&V> {
+        self.search_pair(key, cmp).map(|(_, v)| v)
     }
 
     // 返回AVL树中的最小键值对
@@ -302,39 +663,322 @@ impl<K: PartialOrd + Clone, V> Node<K, V> {
             .map_or((&self.key, &self.value), |right| right.max_pair())
     }
 
+    // 返回中序遍历中第n个(从0开始)键值对
+    pub fn select(&self, n: usize) -> Option<(&K, &V)> {
+        let left_size = Self::size(&self.left) as usize;
+        if n < left_size {
+            self.left.as_ref().and_then(|left| left.select(n))
+        } else if n == left_size {
+            Some((&self.key, &self.value))
+        } else {
+            self.right
+                .as_ref()
+                .and_then(|right| right.select(n - left_size - 1))
+        }
+    }
+
+    // 返回树中严格小于key的键的个数,key可以不存在树中
+    pub fn rank(&self, key: &K, cmp: &Comparator<K>) -> usize {
+        match cmp.compare(&self.key, key) {
+            Ordering::Less => {
+                let right = self.right.as_ref().map_or(0, |right| right.rank(key, cmp));
+                Self::size(&self.left) as usize + 1 + right
+            }
+            Ordering::Greater => self.left.as_ref().map_or(0, |left| left.rank(key, cmp)),
+            Ordering::Equal => Self::size(&self.left) as usize,
+        }
+    }
+
+    // 返回第一个大于key的键值对及其排名，排名在同一次下降中由经过的左子树大小累加得到
+    pub fn successor_indexed<'a>(
+        root: &'a Link<K, V, A>,
+        key: &K,
+        cmp: &Comparator<K>,
+    ) -> Option<(usize, &'a K, &'a V)> {
+        let mut link = root;
+        let mut offset = 0; // 当前子树之前的键的个数
+        let mut found = None;
+        while let Some(node) = link {
+            let left_size = Self::size(&node.left) as usize;
+            if cmp.compare(&node.key, key) == Ordering::Greater {
+                found = Some((offset + left_size, &node.key, &node.value));
+                link = &node.left;
+            } else {
+                offset += left_size + 1;
+                link = &node.right;
+            }
+        }
+        found
+    }
+
+    // 返回第一个小于key的键值对及其排名，排名在同一次下降中由经过的左子树大小累加得到
+    pub fn predecessor_indexed<'a>(
+        root: &'a Link<K, V, A>,
+        key: &K,
+        cmp: &Comparator<K>,
+    ) -> Option<(usize, &'a K, &'a V)> {
+        let mut link = root;
+        let mut offset = 0; // 当前子树之前的键的个数
+        let mut found = None;
+        while let Some(node) = link {
+            let left_size = Self::size(&node.left) as usize;
+            if cmp.compare(&node.key, key) == Ordering::Less {
+                found = Some((offset + left_size, &node.key, &node.value));
+                offset += left_size + 1;
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+        found
+    }
+
     // 判断节点是否满足AVL树的性质
-    fn is_avl_node(&self) -> bool {
+    fn is_avl_node(&self, cmp: &Comparator<K>) -> bool {
         if self.is_leaf() {
             return true;
         }
-        if !self.left.as_ref().map_or(true, |succ| succ.key < self.key) {
+        if self
+            .left
+            .as_ref()
+            .is_some_and(|succ| cmp.compare(&succ.key, &self.key) != Ordering::Less)
+        {
             return false;
         }
-        if !self.right.as_ref().map_or(true, |succ| succ.key > self.key) {
+        if self
+            .right
+            .as_ref()
+            .is_some_and(|succ| cmp.compare(&succ.key, &self.key) != Ordering::Greater)
+        {
             return false;
         }
         let balance = self.diff_of_height();
-        if balance > 1 || balance < -1 {
+        if !(-1..=1).contains(&balance) {
             return false;
         }
         true
     }
 
+    // 逐个节点检查AVL树的性质，返回第一处违反性质的位置；成功时返回子树的实际高度和节点个数
+    // lower和upper为祖先限定的开区间，子树中所有的键都必须位于其中，None表示该侧不限制
+    pub fn validate(
+        root: &Link<K, V, A>,
+        lower: Option<&K>,
+        upper: Option<&K>,
+        cmp: &Comparator<K>,
+    ) -> Result<(u32, u32), AvlError<K>> {
+        let node = match root {
+            None => return Ok((0, 0)),
+            Some(node) => node,
+        };
+        let error_key = || node.key.clone();
+        let in_range = |key: &K, lower: Option<&K>, upper: Option<&K>| {
+            lower.map_or(true, |lower| cmp.compare(key, lower) == Ordering::Greater)
+                && upper.map_or(true, |upper| cmp.compare(key, upper) == Ordering::Less)
+        };
+        let unordered_left = node
+            .left
+            .as_ref()
+            .is_some_and(|succ| !in_range(&succ.key, lower, Some(&node.key)));
+        let unordered_right = node
+            .right
+            .as_ref()
+            .is_some_and(|succ| !in_range(&succ.key, Some(&node.key), upper));
+        if unordered_left || unordered_right {
+            return Err(AvlError::UnorderedKeys { key: error_key() });
+        }
+        let (left_height, left_size) = Self::validate(&node.left, lower, Some(&node.key), cmp)?;
+        let (right_height, right_size) = Self::validate(&node.right, Some(&node.key), upper, cmp)?;
+        let height = max(left_height, right_height) + 1;
+        if u32::from(node.height) != height {
+            return Err(AvlError::HeightMismatch {
+                key: error_key(),
+                stored: u32::from(node.height),
+                actual: height,
+            });
+        }
+        let size = left_size + right_size + 1;
+        if node.size != size {
+            return Err(AvlError::SizeMismatch {
+                key: error_key(),
+                stored: node.size,
+                actual: size,
+            });
+        }
+        let balance = left_height as i32 - right_height as i32;
+        if !(-1..=1).contains(&balance) {
+            return Err(AvlError::BalanceViolation {
+                key: error_key(),
+                balance,
+            });
+        }
+        Ok((height, size))
+    }
+
     // 判断是否为AVL树
-    pub fn is_avl_tree(root: &Link<K, V>) -> bool {
+    pub fn is_avl_tree(root: &Link<K, V, A>, cmp: &Comparator<K>) -> bool {
         match root {
             None => true,
             Some(node) => {
-                if !node.is_avl_node() {
+                if !node.is_avl_node(cmp) {
                     return false;
                 }
-                Self::is_avl_tree(&node.left) && Self::is_avl_tree(&node.right)
+                Self::is_avl_tree(&node.left, cmp) && Self::is_avl_tree(&node.right, cmp)
             }
         }
     }
+
+    // 修改键对应的值，并重新计算查找路径上的聚合值，返回是否找到了键
+    pub fn update_value<F: FnOnce(&mut V)>(&mut self, key: &K, f: F, cmp: &Comparator<K>) -> bool {
+        let found = match cmp.compare(&self.key, key) {
+            Ordering::Less => self
+                .right
+                .as_mut()
+                .is_some_and(|right| right.update_value(key, f, cmp)),
+            Ordering::Greater => self
+                .left
+                .as_mut()
+                .is_some_and(|left| left.update_value(key, f, cmp)),
+            Ordering::Equal => {
+                f(&mut self.value);
+                true
+            }
+        };
+        if found {
+            self.update_metadata();
+        }
+        found
+    }
+}
+
+// 交出值的可变借用的操作只提供给不维护聚合值的节点，否则修改值之后聚合值会失效
+impl<K: Ord + Clone, V> Node<K, V> {
+    // 按键的升序对范围内的每个键值对调用f，跳过范围外的子树
+    pub fn range_for_each_mut<R, F>(
+        root: &mut Link<K, V>,
+        range: &R,
+        f: &mut F,
+        cmp: &Comparator<K>,
+    ) where
+        R: RangeBounds<K>,
+        F: FnMut(&K, &mut V),
+    {
+        if let Some(node) = root {
+            let Node {
+                key,
+                value,
+                left,
+                right,
+                ..
+            } = &mut **node;
+            let go_left = match range.start_bound() {
+                Bound::Included(k) | Bound::Excluded(k) => cmp.compare(key, k) == Ordering::Greater,
+                Bound::Unbounded => true,
+            };
+            let go_right = match range.end_bound() {
+                Bound::Included(k) | Bound::Excluded(k) => cmp.compare(key, k) == Ordering::Less,
+                Bound::Unbounded => true,
+            };
+            if go_left {
+                Self::range_for_each_mut(left, range, f, cmp);
+            }
+            if cmp.above_lower_bound(key, range.start_bound())
+                && cmp.below_upper_bound(key, range.end_bound())
+            {
+                f(key, value);
+            }
+            if go_right {
+                Self::range_for_each_mut(right, range, f, cmp);
+            }
+        }
+    }
+
+    // 返回查找的键值对，其中值为可变借用
+    pub fn search_pair_mut(&mut self, key: &K, cmp: &Comparator<K>) -> Option<(&K, &mut V)> {
+        match cmp.compare(&self.key, key) {
+            Ordering::Less => self
+                .right
+                .as_mut()
+                .and_then(|right| right.search_pair_mut(key, cmp)),
+            Ordering::Greater => self
+                .left
+                .as_mut()
+                .and_then(|left| left.search_pair_mut(key, cmp)),
+            Ordering::Equal => Some((&self.key, &mut self.value)),
+        }
+    }
+
+    // 在一次下降中找出多个键的值的可变借用，order为keys的下标按键升序排列，找到的值写入out中对应的位置
+    // 在每个节点处按键的顺序把order分为小于、等于和大于节点的三段，左右两段分别交给互不重叠的左右子树
+    pub fn search_many_mut<'a>(
+        root: &'a mut Link<K, V>,
+        keys: &[&K],
+        order: &[usize],
+        out: &mut [Option<&'a mut V>],
+        cmp: &Comparator<K>,
+    ) {
+        let node = match root {
+            Some(node) if !order.is_empty() => node,
+            _ => return,
+        };
+        let (key, value, left, right) = node.split_mut();
+        let less = order.partition_point(|&i| cmp.compare(keys[i], key) == Ordering::Less);
+        let greater = order.partition_point(|&i| cmp.compare(keys[i], key) != Ordering::Greater);
+        Self::search_many_mut(left, keys, &order[..less], out, cmp);
+        if less < greater {
+            out[order[less]] = Some(value);
+        }
+        Self::search_many_mut(right, keys, &order[greater..], out, cmp);
+    }
+
+    // 返回AVL树中最小的键及其值的可变借用
+    pub fn min_pair_mut(&mut self) -> (&K, &mut V) {
+        match self.left {
+            Some(ref mut left) => left.min_pair_mut(),
+            None => (&self.key, &mut self.value),
+        }
+    }
+
+    // 返回AVL树中最大的键及其值的可变借用
+    pub fn max_pair_mut(&mut self) -> (&K, &mut V) {
+        match self.right {
+            Some(ref mut right) => right.max_pair_mut(),
+            None => (&self.key, &mut self.value),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Add<Output = V> + Clone> Node<K, V, Sum<V>> {
+    // 返回范围内所有值的和，范围为空时返回None
+    // 找到第一个位于范围内的节点后，左右两侧各只剩一个边界，完全落在范围内的子树直接读取子树和
+    pub fn range_sum(
+        root: &Link<K, V, Sum<V>>,
+        lower: Bound<&K>,
+        upper: Bound<&K>,
+        cmp: &Comparator<K>,
+    ) -> Option<V> {
+        let node = root.as_ref()?;
+        if !cmp.above_lower_bound(&node.key, lower) {
+            return Self::range_sum(&node.right, lower, upper, cmp);
+        }
+        if !cmp.below_upper_bound(&node.key, upper) {
+            return Self::range_sum(&node.left, lower, upper, cmp);
+        }
+        let left = match lower {
+            Bound::Unbounded => node.left.as_ref().map(|left| left.agg.clone()),
+            _ => Self::range_sum(&node.left, lower, Bound::Unbounded, cmp).map(Sum),
+        };
+        let right = match upper {
+            Bound::Unbounded => node.right.as_ref().map(|right| right.agg.clone()),
+            _ => Self::range_sum(&node.right, Bound::Unbounded, upper, cmp).map(Sum),
+        };
+        let Sum(sum) = Sum::combine(left.as_ref(), &node.value, right.as_ref());
+        Some(sum)
+    }
 }
 
-impl<K: PartialOrd + ToString, V: ToString> ToString for Node<K, V> {
+#[allow(clippy::to_string_trait_impl)]
+impl<K: Ord + ToString, V: ToString> ToString for Node<K, V> {
     fn to_string(&self) -> String {
         format!(
             "[K: {}, V: {}, L: {}, R: {}]",
@@ -346,7 +990,7 @@ impl<K: PartialOrd + ToString, V: ToString> ToString for Node<K, V> {
     }
 }
 
-fn to_string<K: PartialOrd + ToString, V: ToString>(node: &Link<K, V>) -> String {
+fn to_string<K: Ord + ToString, V: ToString>(node: &Link<K, V>) -> String {
     match node {
         None => "Ø".to_string(),
         Some(box_node) => box_node.to_string(),
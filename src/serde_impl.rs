@@ -0,0 +1,49 @@
+use crate::AVLTree;
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+use core::fmt;
+use core::marker::PhantomData;
+
+// 按键的升序序列化为键值对序列
+impl<K, V> Serialize for AVLTree<K, V>
+where
+    K: Serialize + Ord + Clone,
+    V: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+// 从键值对序列反序列化，逐个插入新树，因此输入无需有序
+impl<'de, K, V> Deserialize<'de> for AVLTree<K, V>
+where
+    K: Deserialize<'de> + Ord + Clone,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(AVLTreeVisitor(PhantomData))
+    }
+}
+
+struct AVLTreeVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> Visitor<'de> for AVLTreeVisitor<K, V>
+where
+    K: Deserialize<'de> + Ord + Clone,
+    V: Deserialize<'de>,
+{
+    type Value = AVLTree<K, V>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of key-value pairs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut tree = AVLTree::new();
+        while let Some((key, value)) = seq.next_element()? {
+            tree.insert(key, value);
+        }
+        Ok(tree)
+    }
+}
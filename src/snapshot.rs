@@ -0,0 +1,168 @@
+use crate::compare::Comparator;
+use crate::node::{Link, Node};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+// AVL树的根节点，附带最小、最大节点的指针和按键升序排列的扁平快照的缓存，都在首次读取时填入
+// 对根节点的任何可变访问都要经过DerefMut，在那里清除缓存，因此缓存不会与树的内容不一致
+pub struct CachedRoot<K, V> {
+    link: Link<K, V>,
+    // 最小和最大节点的指针，为空表示尚未缓存；节点只有经过DerefMut才会被修改、移动或释放，因此缓存的指针始终有效
+    min: AtomicPtr<Node<K, V>>,
+    max: AtomicPtr<Node<K, V>>,
+    // 快照的堆指针，为空表示尚未构建；多个线程同时构建时只保留先写入的一份，因此只读的树仍可在线程间共享
+    pairs: AtomicPtr<Vec<(K, V)>>,
+    // 快照由CachedRoot拥有，Send和Sync在下面单独实现
+    _owns: PhantomData<*const Vec<(K, V)>>,
+}
+
+// SAFETY: 快照与节点一样由CachedRoot独占，随它一起移动到其它线程
+unsafe impl<K: Send, V: Send> Send for CachedRoot<K, V> {}
+// SAFETY: 共享时只能通过原子操作发布快照，其它线程构建的快照最终在拥有者的线程中释放，因此还要求K和V是Send
+unsafe impl<K: Send + Sync, V: Send + Sync> Sync for CachedRoot<K, V> {}
+
+impl<K, V> CachedRoot<K, V> {
+    pub fn new(link: Link<K, V>) -> Self {
+        CachedRoot {
+            link,
+            min: AtomicPtr::new(ptr::null_mut()),
+            max: AtomicPtr::new(ptr::null_mut()),
+            pairs: AtomicPtr::new(ptr::null_mut()),
+            _owns: PhantomData,
+        }
+    }
+
+    // 返回最小的节点，未缓存时沿左侧路径下降一次并缓存
+    pub fn min_node(&self) -> Option<&Node<K, V>> {
+        Self::extreme(&self.link, &self.min, Node::min_node)
+    }
+
+    // 返回最大的节点，未缓存时沿右侧路径下降一次并缓存
+    pub fn max_node(&self) -> Option<&Node<K, V>> {
+        Self::extreme(&self.link, &self.max, Node::max_node)
+    }
+
+    fn extreme<'a>(
+        link: &'a Link<K, V>,
+        cache: &AtomicPtr<Node<K, V>>,
+        descend: fn(&Node<K, V>) -> &Node<K, V>,
+    ) -> Option<&'a Node<K, V>> {
+        let cached = cache.load(Ordering::Acquire);
+        if !cached.is_null() {
+            // SAFETY: 指针由link中节点的共享借用得到，之后对节点的任何写入、移动和释放都要先经过DerefMut清空缓存
+            return Some(unsafe { &*cached });
+        }
+        let node = descend(link.as_deref()?);
+        cache.store(
+            node as *const Node<K, V> as *mut Node<K, V>,
+            Ordering::Release,
+        );
+        Some(node)
+    }
+
+    // 返回缓存的快照，尚未构建时由build构建一次
+    pub fn pairs<F: FnOnce() -> Vec<(K, V)>>(&self, build: F) -> &[(K, V)] {
+        let mut pairs = self.pairs.load(Ordering::Acquire);
+        if pairs.is_null() {
+            let built = Box::into_raw(Box::new(build()));
+            pairs = match self.pairs.compare_exchange(
+                ptr::null_mut(),
+                built,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => built,
+                Err(winner) => {
+                    // SAFETY: built没有发布给其它线程，这里收回所有权并释放
+                    drop(unsafe { Box::from_raw(built) });
+                    winner
+                }
+            };
+        }
+        // SAFETY: 指针由Box::into_raw得到，只在持有&mut self时(clear_pairs)才会被释放，因此在&self的生命周期内有效
+        unsafe { &*pairs }
+    }
+
+    // 返回已缓存的键值对个数，尚未构建时返回0
+    pub fn cached_len(&self) -> usize {
+        let pairs = self.pairs.load(Ordering::Acquire);
+        // SAFETY: 同pairs
+        unsafe { pairs.as_ref() }.map_or(0, Vec::len)
+    }
+
+    // 释放缓存的快照
+    fn clear_pairs(&mut self) {
+        let pairs = core::mem::replace(self.pairs.get_mut(), ptr::null_mut());
+        if !pairs.is_null() {
+            // SAFETY: 持有&mut self，不存在借用快照的&self，指针由Box::into_raw得到且只释放一次
+            drop(unsafe { Box::from_raw(pairs) });
+        }
+    }
+}
+
+impl<K, V> Deref for CachedRoot<K, V> {
+    type Target = Link<K, V>;
+
+    fn deref(&self) -> &Link<K, V> {
+        &self.link
+    }
+}
+
+impl<K, V> DerefMut for CachedRoot<K, V> {
+    // 可变访问可能修改树的结构或值，先清除最小、最大节点的指针和快照
+    fn deref_mut(&mut self) -> &mut Link<K, V> {
+        *self.min.get_mut() = ptr::null_mut();
+        *self.max.get_mut() = ptr::null_mut();
+        self.clear_pairs();
+        &mut self.link
+    }
+}
+
+impl<K, V> Drop for CachedRoot<K, V> {
+    fn drop(&mut self) {
+        self.clear_pairs();
+    }
+}
+
+// AVL树按键升序排列的扁平快照，借用树中缓存的键值对数组，可用二分查找反复读取
+// 快照借用了整棵树，树在快照存活期间无法被修改；树被修改后缓存被清除，下次读取时重新构建
+pub struct SortedPairs<'a, K, V> {
+    pairs: &'a [(K, V)],
+    cmp: &'a Comparator<K>, // 树的比较规则，二分查找时使用
+}
+
+impl<'a, K: Ord, V> SortedPairs<'a, K, V> {
+    pub fn new(pairs: &'a [(K, V)], cmp: &'a Comparator<K>) -> Self {
+        SortedPairs { pairs, cmp }
+    }
+
+    // 返回按键升序排列的键值对切片
+    pub fn as_slice(&self) -> &'a [(K, V)] {
+        self.pairs
+    }
+
+    // 返回快照中键值对的个数
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    // 判断快照是否为空
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    // 二分查找键在切片中的位置，找不到时返回可插入的位置
+    pub fn binary_search(&self, key: &K) -> Result<usize, usize> {
+        self.pairs
+            .binary_search_by(|(k, _)| self.cmp.compare(k, key))
+    }
+
+    // 二分查找键对应的值
+    pub fn get(&self, key: &K) -> Option<&'a V> {
+        self.binary_search(key).ok().map(|i| &self.pairs[i].1)
+    }
+}
@@ -0,0 +1,175 @@
+use crate::compare::Comparator;
+use crate::node::{Link, Node, Sum};
+use alloc::boxed::Box;
+use core::iter::FromIterator;
+use core::ops::{Add, RangeBounds};
+
+/// 在每个节点中维护子树所有值之和的AVL树，范围求和的时间复杂度为O(log n)
+/// 子树和随高度、子树大小一起在插入、删除和旋转时更新，因此不提供值的可变借用，修改值使用update
+/// 不需要范围求和时使用AVLTree，它的节点不保存子树和
+/// # Example
+/// ```
+/// use an_ok_avl_tree::SumTree;
+/// let mut tree: SumTree<i32, i32> = (1..=6).map(|key| (key, key * 10)).collect();
+/// assert_eq!(tree.sum_range(2..=4), Some(90));
+/// tree.update(&3, |value| *value = 0);
+/// assert_eq!(tree.sum_range(2..=4), Some(60));
+/// ```
+pub struct SumTree<K, V> {
+    root: Link<K, V, Sum<V>>,
+    cmp: Comparator<K>,
+}
+
+impl<K, V> SumTree<K, V> {
+    /// 返回键值对的个数
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// tree.insert(1, 10);
+    /// assert_eq!(tree.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        Node::size(&self.root) as usize
+    }
+
+    /// 判断是否为空
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// assert!(tree.is_empty());
+    /// tree.insert(1, 10);
+    /// assert!(!tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+}
+
+impl<K: Ord + Clone, V: Add<Output = V> + Clone> SumTree<K, V> {
+    /// 构建一棵空的SumTree
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::SumTree;
+    /// let tree: SumTree<i32, i32> = SumTree::new();
+    /// assert_eq!(tree.sum_range(..), None);
+    /// ```
+    pub fn new() -> Self {
+        SumTree {
+            root: None,
+            cmp: Comparator::Natural,
+        }
+    }
+
+    /// 插入键值对，键已存在时替换原来的值，时间复杂度O(log n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// tree.insert(1, 10);
+    /// tree.insert(1, 20);
+    /// assert_eq!(tree.get(&1), Some(&20));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) {
+        self.root = Some(match self.root.take() {
+            None => Box::new(Node::new(key, value)),
+            Some(node) => node.insert(key, value, &self.cmp),
+        });
+    }
+
+    /// 删除键对应的键值对，键不存在时不做任何修改，时间复杂度O(log n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// tree.insert(1, 10);
+    /// tree.delete(1);
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn delete(&mut self, key: K) {
+        if let Some(node) = self.root.take() {
+            self.root = node.delete(key, &self.cmp);
+        }
+    }
+
+    /// 用f修改键对应的值，并更新查找路径上的子树和，返回键是否存在，时间复杂度O(log n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// tree.insert(1, 10);
+    /// assert!(tree.update(&1, |value| *value += 5));
+    /// assert!(!tree.update(&2, |value| *value += 5));
+    /// assert_eq!(tree.get(&1), Some(&15));
+    /// ```
+    pub fn update<F: FnOnce(&mut V)>(&mut self, key: &K, f: F) -> bool {
+        match self.root.as_mut() {
+            Some(node) => node.update_value(key, f, &self.cmp),
+            None => false,
+        }
+    }
+
+    /// 根据键查找对应的值，找不到返回None
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// tree.insert(1, 10);
+    /// assert_eq!(tree.get(&1), Some(&10));
+    /// assert_eq!(tree.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.root
+            .as_ref()
+            .and_then(|node| node.search(key, &self.cmp))
+    }
+
+    /// 判断键是否存在
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::SumTree;
+    /// let mut tree = SumTree::new();
+    /// tree.insert(1, 10);
+    /// assert!(tree.contains(&1));
+    /// assert!(!tree.contains(&2));
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// 返回范围内所有值的和，范围为空时返回None
+    /// 完全落在范围内的子树直接读取节点中的子树和，时间复杂度O(log n)
+    /// # Example
+    /// ```
+    /// use an_ok_avl_tree::SumTree;
+    /// let tree: SumTree<i32, i32> = (1..=6).map(|key| (key, key * 10)).collect();
+    /// assert_eq!(tree.sum_range(2..=4), Some(90));
+    /// assert_eq!(tree.sum_range(..), Some(210));
+    /// assert_eq!(tree.sum_range(7..), None);
+    /// ```
+    pub fn sum_range<R: RangeBounds<K>>(&self, range: R) -> Option<V> {
+        Node::range_sum(
+            &self.root,
+            range.start_bound(),
+            range.end_bound(),
+            &self.cmp,
+        )
+    }
+}
+
+impl<K: Ord + Clone, V: Add<Output = V> + Clone> Default for SumTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V: Add<Output = V> + Clone> FromIterator<(K, V)> for SumTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = SumTree::new();
+        for (key, value) in iter {
+            tree.insert(key, value);
+        }
+        tree
+    }
+}
@@ -0,0 +1,28 @@
+// 测试crate本身不引入std的prelude，只通过core和alloc使用AVL树的核心接口
+#![no_std]
+
+extern crate alloc;
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use an_ok_avl_tree::AVLTree;
+
+    #[test]
+    fn core_api_without_std() {
+        let mut tree = AVLTree::new();
+        for key in (0..100).rev() {
+            tree.insert(key, key * 2);
+        }
+        tree.delete(50);
+        assert!(tree.is_avl_tree());
+        assert_eq!(tree.get(&10), Some(&20));
+        assert_eq!(tree.get(&50), None);
+        assert_eq!(tree.min_pair(), Some((&0, &0)));
+        assert_eq!(tree.range(10..13).count(), 3);
+        let keys: Vec<i32> = tree.keys().copied().collect();
+        assert_eq!(keys.len(), 99);
+        let pairs: Vec<(i32, i32)> = tree.into_iter().collect();
+        assert_eq!(pairs[0], (0, 0));
+    }
+}
@@ -1,7 +1,22 @@
 #[cfg(test)]
 mod tests {
-    use an_ok_avl_tree::AVLTree;
+    use an_ok_avl_tree::{AVLTree, RotationKind, RotationStep, SumTree, TryInsertError};
+    use std::cmp::{Ordering, Reverse};
     use std::collections::Bound;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+    // 简单的线性同余伪随机数生成器，避免引入外部依赖
+    fn random_keys(n: usize, seed: u64) -> Vec<i32> {
+        let mut state = seed;
+        (0..n)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) as i32
+            })
+            .collect()
+    }
 
     #[test]
     fn insert_delete() {
@@ -203,4 +218,2336 @@ mod tests {
         tree.insert(4, 'd');
         assert_eq!(tree.to_string(), String::from("[K: 2, V: b, L: [K: 1, V: a, L: Ø, R: Ø], R: [K: 3, V: c, L: Ø, R: [K: 4, V: d, L: Ø, R: Ø]]]"))
     }
+
+    #[test]
+    fn into_iter() {
+        let mut tree = AVLTree::new();
+        for i in [5, 3, 8, 1, 4, 7, 9, 2, 6].iter() {
+            tree.insert(*i, i.to_string());
+        }
+        let res: Vec<(i32, String)> = tree.into_iter().collect();
+        let expected: Vec<(i32, String)> = (1..10).map(|i| (i, i.to_string())).collect();
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn assign_range() {
+        let mut tree = AVLTree::new();
+        for i in 0..10 {
+            tree.insert(i, i);
+        }
+        tree.assign_range(2..=6, vec![10, 20, 30]);
+        let res: Vec<(&i32, &i32)> = tree.inorder_iter().collect();
+        assert_eq!(
+            res,
+            vec![
+                (&0, &0),
+                (&1, &1),
+                (&2, &10),
+                (&3, &20),
+                (&4, &30),
+                (&5, &5),
+                (&6, &6),
+                (&7, &7),
+                (&8, &8),
+                (&9, &9)
+            ]
+        );
+        tree.assign_range(8.., vec![80, 90, 100]);
+        assert_eq!(tree.get(&8), Some(&80));
+        assert_eq!(tree.get(&9), Some(&90));
+        assert!(tree.is_avl_tree());
+    }
+
+    #[test]
+    fn iter() {
+        let mut tree = AVLTree::new();
+        for key in random_keys(1000, 42) {
+            tree.insert(key, -key);
+        }
+        let lazy: Vec<(&i32, &i32)> = tree.iter().collect();
+        let eager: Vec<(&i32, &i32)> = tree.inorder_iter().collect();
+        assert_eq!(lazy, eager);
+    }
+
+    #[test]
+    fn insert_traced() {
+        /*
+                 3              3             2
+                /              /             / \
+               1     ->       2     ->      1   3
+                \            /
+                 2          1
+        */
+        let mut tree = AVLTree::new();
+        assert!(tree.insert_traced(3, 'c').is_empty());
+        assert!(tree.insert_traced(1, 'a').is_empty());
+        let trace = tree.insert_traced(2, 'b');
+        assert_eq!(
+            trace,
+            vec![RotationStep {
+                pivot: 3,
+                kind: RotationKind::LR
+            }]
+        );
+        assert!(tree.is_avl_tree());
+        let res: Vec<(&i32, &char)> = tree.preorder_iter().collect();
+        assert_eq!(res, vec![(&2, &'b'), (&1, &'a'), (&3, &'c')]);
+        assert_eq!(tree.insert_traced(2, 'z'), vec![]);
+    }
+
+    #[test]
+    fn values_mut() {
+        let mut tree = AVLTree::new();
+        for i in 0..100 {
+            tree.insert(i, i);
+        }
+        for value in tree.values_mut() {
+            *value *= 2;
+        }
+        for i in 0..100 {
+            assert_eq!(tree.get(&i), Some(&(i * 2)));
+        }
+        assert_eq!(tree.values_mut().count(), 100);
+    }
+
+    #[test]
+    fn merge_iter() {
+        let mut a = AVLTree::new();
+        let mut b = AVLTree::new();
+        for i in (0..10).step_by(2) {
+            a.insert(i, 'a');
+        }
+        for i in (0..10).step_by(3) {
+            b.insert(i, 'b');
+        }
+        let res: Vec<(&i32, &char)> = a.merge_iter(&b).collect();
+        assert_eq!(
+            res,
+            vec![
+                (&0, &'a'),
+                (&2, &'a'),
+                (&3, &'b'),
+                (&4, &'a'),
+                (&6, &'a'),
+                (&8, &'a'),
+                (&9, &'b')
+            ]
+        );
+        let res: Vec<(&i32, &char)> = b.merge_iter(&a).take(2).collect();
+        assert_eq!(res, vec![(&0, &'b'), (&2, &'a')]);
+    }
+
+    #[test]
+    fn from_iter() {
+        let pairs = vec![(3, 'c'), (1, 'a'), (4, 'd'), (2, 'b'), (3, 'z'), (5, 'e')];
+        let tree: AVLTree<i32, char> = pairs.into_iter().collect();
+        assert!(tree.is_avl_tree());
+        assert_eq!(tree.get(&3), Some(&'z'));
+        let res: Vec<(&i32, &char)> = tree.iter().collect();
+        assert_eq!(
+            res,
+            vec![(&1, &'a'), (&2, &'b'), (&3, &'z'), (&4, &'d'), (&5, &'e')]
+        );
+    }
+
+    #[test]
+    fn prune_to_bytes() {
+        let mut tree = AVLTree::new();
+        for i in 0..10 {
+            tree.insert(i, vec![0u8; 1000]);
+        }
+        tree.prune_to_bytes(5500, |value| value.len(), true);
+        assert!(tree.is_avl_tree());
+        assert_eq!(
+            tree.keys().cloned().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+        tree.prune_to_bytes(3500, |value| value.len(), false);
+        assert!(tree.is_avl_tree());
+        assert_eq!(tree.keys().cloned().collect::<Vec<_>>(), vec![2, 3, 4]);
+        tree.prune_to_bytes(0, |value| value.len(), false);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn extend() {
+        let mut tree: AVLTree<i32, i32> = (0..10).map(|i| (i, i)).collect();
+        tree.extend((5..15).map(|i| (i, i * 10)));
+        assert!(tree.is_avl_tree());
+        for i in 0..5 {
+            assert_eq!(tree.get(&i), Some(&i));
+        }
+        for i in 5..15 {
+            assert_eq!(tree.get(&i), Some(&(i * 10)));
+        }
+        assert_eq!(tree.iter().count(), 15);
+    }
+
+    #[test]
+    fn successor_predecessor_indexed() {
+        let tree: AVLTree<i32, i32> = (0..50).map(|i| (i * 2, i)).collect();
+        let keys: Vec<&i32> = tree.keys().collect();
+        for probe in -1..101 {
+            let pair =
+                |found: Option<(usize, &i32, &i32)>| found.map(|(_, key, value)| (*key, *value));
+            let successor = tree.successor(&probe).map(|(key, value)| (*key, *value));
+            assert_eq!(pair(tree.successor_indexed(&probe)), successor);
+            let predecessor = tree.predecessor(&probe).map(|(key, value)| (*key, *value));
+            assert_eq!(pair(tree.predecessor_indexed(&probe)), predecessor);
+            match tree.successor_indexed(&probe) {
+                Some((rank, key, value)) => {
+                    assert!(*key > probe);
+                    assert_eq!(keys[rank], key);
+                    assert_eq!(rank, tree.rank(key));
+                    assert_eq!(*value, key / 2);
+                }
+                None => assert!(probe >= 98),
+            }
+            match tree.predecessor_indexed(&probe) {
+                Some((rank, key, _)) => {
+                    assert!(*key < probe);
+                    assert_eq!(keys[rank], key);
+                    assert_eq!(rank, tree.rank(key));
+                }
+                None => assert!(probe <= 0),
+            }
+        }
+    }
+
+    #[test]
+    fn clone() {
+        let tree: AVLTree<i32, char> = vec![(3, 'c'), (2, 'b'), (1, 'a'), (4, 'd')]
+            .into_iter()
+            .collect();
+        let mut cloned = tree.clone();
+        let res: Vec<(&i32, &char)> = cloned.preorder_iter().collect();
+        let expected: Vec<(&i32, &char)> = tree.preorder_iter().collect();
+        assert_eq!(res, expected);
+
+        cloned.insert(5, 'e');
+        cloned.insert(1, 'z');
+        assert!(tree.is_avl_tree());
+        assert!(cloned.is_avl_tree());
+        assert_eq!(tree.get(&1), Some(&'a'));
+        assert!(!tree.contains(&5));
+        assert_eq!(cloned.get(&1), Some(&'z'));
+        assert!(cloned.contains(&5));
+    }
+
+    #[test]
+    fn update_keys() {
+        let mut tree: AVLTree<i32, i32> = (0..10).map(|i| (i, i)).collect();
+        let mut seen = Vec::new();
+        let count = tree.update_keys(vec![1, 3, 5, 11, -1], |key, value| {
+            seen.push(*key);
+            *value *= 100;
+        });
+        assert_eq!(count, 3);
+        assert_eq!(seen, vec![1, 3, 5]);
+        for i in 0..10 {
+            let expected = if i == 1 || i == 3 || i == 5 {
+                i * 100
+            } else {
+                i
+            };
+            assert_eq!(tree.get(&i), Some(&expected));
+        }
+        assert!(tree.is_avl_tree());
+    }
+
+    #[test]
+    fn debug() {
+        let mut tree = AVLTree::new();
+        assert_eq!(format!("{:?}", tree), "{}");
+        tree.insert(3, 'c');
+        tree.insert(1, 'a');
+        tree.insert(2, 'b');
+        assert_eq!(format!("{:?}", tree), "{1: 'a', 2: 'b', 3: 'c'}");
+        let tree: AVLTree<&str, Vec<i32>> =
+            vec![("y", vec![2]), ("x", vec![1])].into_iter().collect();
+        assert_eq!(format!("{:?}", tree), r#"{"x": [1], "y": [2]}"#);
+    }
+
+    #[test]
+    fn to_adjacency_list() {
+        /*
+                 2
+                / \
+               1   3
+                    \
+                     4
+        */
+        let mut tree = AVLTree::new();
+        tree.insert(3, 'c');
+        tree.insert(2, 'b');
+        tree.insert(1, 'a');
+        tree.insert(4, 'd');
+        assert_eq!(
+            tree.to_adjacency_list(),
+            vec![
+                (2, Some(1), Some(3)),
+                (1, None, None),
+                (3, None, Some(4)),
+                (4, None, None)
+            ]
+        );
+        assert!(AVLTree::<i32, char>::new().to_adjacency_list().is_empty());
+    }
+
+    #[test]
+    fn eq() {
+        let mut a = AVLTree::new();
+        let mut b = AVLTree::new();
+        for i in 0..20 {
+            a.insert(i, i * 3);
+        }
+        for i in (0..20).rev() {
+            b.insert(i, i * 3);
+        }
+        assert_ne!(a.to_adjacency_list(), b.to_adjacency_list());
+        assert_eq!(a, b);
+        b.insert(7, 0);
+        assert_ne!(a, b);
+        b.insert(7, 21);
+        assert_eq!(a, b);
+        b.insert(20, 60);
+        assert_ne!(a, b);
+        assert_eq!(AVLTree::<i32, i32>::new(), AVLTree::new());
+    }
+
+    #[test]
+    fn width_at_depth() {
+        /*
+                         4
+                       /   \
+                     2       7
+                    / \     /  \
+                   1   3   6    9
+                          /    / \
+                         5    8   10
+        */
+        let tree: AVLTree<i32, i32> = vec![3, 2, 1, 4, 5, 6, 7, 10, 9, 8]
+            .into_iter()
+            .map(|i| (i, i))
+            .collect();
+        assert_eq!(tree.width_at_depth(0), 1);
+        assert_eq!(tree.width_at_depth(1), 2);
+        assert_eq!(tree.width_at_depth(2), 4);
+        assert_eq!(tree.width_at_depth(3), 3);
+        assert_eq!(tree.width_at_depth(4), 0);
+        assert_eq!(AVLTree::<i32, i32>::new().width_at_depth(0), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let tree: AVLTree<i32, String> = (0..10).map(|i| (i, i.to_string())).collect();
+        let json = serde_json::to_string(&tree).unwrap();
+        assert!(json.starts_with(r#"[[0,"0"],[1,"1"],"#));
+        let decoded: AVLTree<i32, String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, tree);
+        assert!(decoded.is_avl_tree());
+
+        let unordered: AVLTree<i32, char> =
+            serde_json::from_str(r#"[[3,"c"],[1,"a"],[2,"b"],[1,"z"]]"#).unwrap();
+        assert!(unordered.is_avl_tree());
+        let res: Vec<(&i32, &char)> = unordered.iter().collect();
+        assert_eq!(res, vec![(&1, &'z'), (&2, &'b'), (&3, &'c')]);
+    }
+
+    #[test]
+    fn difference_update() {
+        let mut a: AVLTree<i32, i32> = (0..100).map(|i| (i, i)).collect();
+        let b: AVLTree<i32, i32> = (50..150).step_by(2).map(|i| (i, -i)).collect();
+        a.difference_update(&b);
+        assert!(a.is_avl_tree());
+        let expected: Vec<i32> = (0..100).filter(|i| *i < 50 || i % 2 == 1).collect();
+        assert_eq!(a.keys().cloned().collect::<Vec<_>>(), expected);
+        assert_eq!(a.get(&51), Some(&51));
+
+        a.difference_update(&AVLTree::new());
+        assert_eq!(a.keys().cloned().collect::<Vec<_>>(), expected);
+        let all = a.clone();
+        a.difference_update(&all);
+        assert!(a.is_empty());
+    }
+
+    #[test]
+    fn depth() {
+        /*
+                         4
+                       /   \
+                     2       7
+                    / \     /  \
+                   1   3   6    9
+                          /    / \
+                         5    8   10
+        */
+        let tree: AVLTree<i32, i32> = vec![3, 2, 1, 4, 5, 6, 7, 10, 9, 8]
+            .into_iter()
+            .map(|i| (i, i))
+            .collect();
+        assert_eq!(tree.depth(&4), Some(0));
+        assert_eq!(tree.depth(&2), Some(1));
+        assert_eq!(tree.depth(&7), Some(1));
+        assert_eq!(tree.depth(&1), Some(2));
+        assert_eq!(tree.depth(&3), Some(2));
+        assert_eq!(tree.depth(&5), Some(3));
+        assert_eq!(tree.depth(&8), Some(3));
+        assert_eq!(tree.depth(&10), Some(3));
+        assert_eq!(tree.depth(&11), None);
+        assert_eq!(AVLTree::<i32, i32>::new().depth(&1), None);
+    }
+
+    #[test]
+    fn intersection_update() {
+        let mut a: AVLTree<i32, i32> = (0..100).map(|i| (i, i)).collect();
+        let b: AVLTree<i32, i32> = (50..150).step_by(2).map(|i| (i, -i)).collect();
+        a.intersection_update(&b);
+        assert!(a.is_avl_tree());
+        let expected: Vec<(i32, i32)> = (50..100).step_by(2).map(|i| (i, i)).collect();
+        assert_eq!(a.clone().into_iter().collect::<Vec<_>>(), expected);
+
+        a.intersection_update(&AVLTree::new());
+        assert!(a.is_empty());
+    }
+
+    #[test]
+    fn entry_at_or_after_before() {
+        let tree: AVLTree<i32, i32> = (0..10).map(|i| (i * 10, i)).collect();
+        assert_eq!(tree.entry_at_or_after(&30), Some((&30, &3)));
+        assert_eq!(tree.entry_at_or_after(&31), Some((&40, &4)));
+        assert_eq!(tree.entry_at_or_after(&-5), Some((&0, &0)));
+        assert_eq!(tree.entry_at_or_after(&91), None);
+        assert_eq!(tree.entry_at_or_before(&30), Some((&30, &3)));
+        assert_eq!(tree.entry_at_or_before(&39), Some((&30, &3)));
+        assert_eq!(tree.entry_at_or_before(&-5), None);
+        assert_eq!(tree.entry_at_or_before(&1000), Some((&90, &9)));
+        for probe in -5..100 {
+            let after = tree.keys().find(|k| **k >= probe);
+            let before = tree.keys().filter(|k| **k <= probe).last();
+            assert_eq!(tree.entry_at_or_after(&probe).map(|(k, _)| k), after);
+            assert_eq!(tree.entry_at_or_before(&probe).map(|(k, _)| k), before);
+        }
+
+        // 只要求K: Ord的泛型代码也可以调用
+        fn first_at_or_after<'a, K: Ord, V>(tree: &'a AVLTree<K, V>, key: &K) -> Option<&'a V> {
+            tree.entry_at_or_after(key).map(|(_, v)| v)
+        }
+        assert_eq!(first_at_or_after(&tree, &55), Some(&6));
+    }
+
+    #[test]
+    fn select() {
+        let mut tree = AVLTree::new();
+        for key in random_keys(500, 7) {
+            tree.insert(key, key);
+        }
+        let sorted: Vec<(&i32, &i32)> = tree.iter().collect();
+        for (n, pair) in sorted.iter().enumerate() {
+            assert_eq!(tree.select(n), Some(*pair));
+        }
+        assert_eq!(tree.select(sorted.len()), None);
+
+        let mut tree: AVLTree<i32, i32> = (0..100).map(|i| (i, i)).collect();
+        for i in (0..100).step_by(3) {
+            tree.delete(i);
+        }
+        tree.insert(1000, 1000);
+        let sorted: Vec<(&i32, &i32)> = tree.iter().collect();
+        for (n, pair) in sorted.iter().enumerate() {
+            assert_eq!(tree.select(n), Some(*pair));
+        }
+        assert_eq!(tree.select(sorted.len()), None);
+        assert_eq!(AVLTree::<i32, i32>::new().select(0), None);
+    }
+
+    #[test]
+    fn rank() {
+        let mut tree = AVLTree::new();
+        for key in random_keys(500, 11) {
+            tree.insert(key, ());
+        }
+        let keys: Vec<i32> = tree.keys().cloned().collect();
+        for n in 0..keys.len() {
+            let (key, _) = tree.select(n).unwrap();
+            assert_eq!(tree.rank(key), n);
+            assert_eq!(
+                tree.rank(&(key + 1)),
+                keys.iter().filter(|k| **k <= *key).count()
+            );
+        }
+        assert_eq!(tree.rank(&i32::MIN), 0);
+        assert_eq!(tree.rank(&i32::MAX), keys.len());
+        assert_eq!(AVLTree::<i32, ()>::new().rank(&0), 0);
+    }
+
+    #[test]
+    fn into_reversed() {
+        let tree: AVLTree<i32, char> = (0..26).map(|i| (i, (b'a' + i as u8) as char)).collect();
+        let mut expected: Vec<(i32, char)> = tree.clone().into_iter().collect();
+        expected.reverse();
+        let reversed = tree.into_reversed();
+        assert!(reversed.is_avl_tree());
+        let res: Vec<(i32, char)> = reversed
+            .inorder_iter()
+            .map(|(Reverse(k), v)| (*k, *v))
+            .collect();
+        assert_eq!(res, expected);
+        assert_eq!(reversed.get(&Reverse(3)), Some(&'d'));
+    }
+
+    #[test]
+    fn range() {
+        let tree: AVLTree<i32, i32> = (0..50).map(|i| (i * 2, i)).collect();
+        for lo in -2..102 {
+            for hi in lo..102 {
+                let expected: Vec<(&i32, &i32)> = tree
+                    .range_pair_iter(Bound::Included(lo), Bound::Excluded(hi))
+                    .collect();
+                assert_eq!(tree.range(lo..hi).collect::<Vec<_>>(), expected);
+                let expected: Vec<(&i32, &i32)> = tree
+                    .range_pair_iter(Bound::Included(lo), Bound::Included(hi))
+                    .collect();
+                assert_eq!(tree.range(lo..=hi).collect::<Vec<_>>(), expected);
+            }
+        }
+        let res: Vec<&i32> = tree
+            .range((Bound::Excluded(3), Bound::Excluded(9)))
+            .map(|(k, _)| k)
+            .collect();
+        assert_eq!(res, vec![&4, &6, &8]);
+    }
+
+    #[test]
+    fn entry_or_insert_with_key() {
+        let mut tree = AVLTree::new();
+        tree.insert(1, "one".to_string());
+        let mut seen = Vec::new();
+        let value = tree.entry(2).unwrap().or_insert_with_key(|key| {
+            seen.push(*key);
+            format!("key-{}", key)
+        });
+        value.push('!');
+        tree.entry(1).unwrap().or_insert_with_key(|key| {
+            seen.push(*key);
+            format!("key-{}", key)
+        });
+        assert_eq!(seen, vec![2]);
+        assert_eq!(tree.get(&1), Some(&"one".to_string()));
+        assert_eq!(tree.get(&2), Some(&"key-2!".to_string()));
+        assert!(tree.is_avl_tree());
+    }
+
+    #[test]
+    fn from_sorted_sources() {
+        let a = vec![(1, 1), (4, 4), (7, 7), (10, 10)];
+        let b = vec![(2, 20), (4, 40), (8, 80)];
+        let c = vec![(0, 300), (4, 400), (4, 400), (10, 1000), (11, 1100)];
+        let mut order = Vec::new();
+        let tree = AVLTree::from_sorted_sources(vec![a, b, c], |key, acc, value| {
+            order.push((*key, value));
+            acc + value
+        });
+        assert!(tree.is_avl_tree());
+        let res: Vec<(i32, i32)> = tree.into_iter().collect();
+        assert_eq!(
+            res,
+            vec![
+                (0, 300),
+                (1, 1),
+                (2, 20),
+                (4, 844),
+                (7, 7),
+                (8, 80),
+                (10, 1010),
+                (11, 1100)
+            ]
+        );
+        assert_eq!(order, vec![(4, 40), (4, 400), (4, 400), (10, 1000)]);
+
+        let empty: AVLTree<i32, i32> =
+            AVLTree::from_sorted_sources(Vec::<Vec<(i32, i32)>>::new(), |_, a, _| a);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "sources must be sorted by key")]
+    fn from_sorted_sources_unsorted() {
+        AVLTree::from_sorted_sources(vec![vec![(2, 2), (1, 1)]], |_, a, _| a);
+    }
+
+    #[test]
+    fn range_pair_iter_rev() {
+        let tree: AVLTree<i32, i32> = (0..30).map(|i| (i * 3, i)).collect();
+        let bounds = |v: i32| vec![Bound::Included(v), Bound::Excluded(v), Bound::Unbounded];
+        for lo in [-1, 0, 10, 12, 45, 87, 90].iter() {
+            for hi in [-1, 0, 10, 12, 45, 87, 90].iter() {
+                for from in bounds(*lo) {
+                    for to in bounds(*hi) {
+                        let mut forward: Vec<(&i32, &i32)> =
+                            tree.range_pair_iter(from, to).collect();
+                        let backward: Vec<(&i32, &i32)> =
+                            tree.range_pair_iter(from, to).rev().collect();
+                        forward.reverse();
+                        assert_eq!(backward, forward);
+                    }
+                }
+            }
+        }
+
+        // 正反两端交替迭代，相遇后不再输出
+        let mut iter = tree.range(3..=15);
+        assert_eq!(iter.next(), Some((&3, &1)));
+        assert_eq!(iter.next_back(), Some((&15, &5)));
+        assert_eq!(iter.next_back(), Some((&12, &4)));
+        assert_eq!(iter.next(), Some((&6, &2)));
+        assert_eq!(iter.next(), Some((&9, &3)));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn as_slice_pairs() {
+        let mut tree: AVLTree<i32, i32> = (0..100).map(|i| (i * 2, i)).collect();
+        {
+            let pairs = tree.as_slice_pairs();
+            assert_eq!(pairs.len(), 100);
+            for probe in -1..201 {
+                assert_eq!(pairs.get(&probe), tree.get(&probe));
+            }
+            assert_eq!(pairs.binary_search(&7), Err(4));
+        }
+        // 未修改时复用缓存的快照
+        let cached = tree.as_slice_pairs().as_slice().as_ptr();
+        assert_eq!(tree.as_slice_pairs().as_slice().as_ptr(), cached);
+        let usage = tree.memory_usage();
+
+        tree.insert(7, -7);
+        tree.delete(0);
+        assert!(tree.memory_usage() < usage);
+        let pairs = tree.as_slice_pairs();
+        assert_eq!(pairs.len(), 100);
+        assert_eq!(pairs.get(&7), Some(&-7));
+        assert_eq!(pairs.get(&0), None);
+        assert!(pairs.as_slice().iter().map(|(k, v)| (k, v)).eq(tree.iter()));
+
+        // 交出值的可变借用也会清除缓存
+        *tree.get_mut(&7).unwrap() = 70;
+        assert_eq!(tree.as_slice_pairs().get(&7), Some(&70));
+        for value in tree.values_mut() {
+            *value += 1;
+        }
+        assert_eq!(tree.as_slice_pairs().get(&7), Some(&71));
+        tree.apply_range(..=7, |_, value| *value = 0);
+        assert_eq!(tree.as_slice_pairs().get(&7), Some(&0));
+        assert!(AVLTree::<i32, i32>::new().as_slice_pairs().is_empty());
+    }
+
+    #[test]
+    fn as_slice_pairs_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        let tree: AVLTree<i32, String> = (0..200).map(|i| (i, i.to_string())).collect();
+        assert_send_sync(&tree);
+        // 多个线程同时构建快照时只保留一份，所有线程读到相同的内容
+        let slices: Vec<usize> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let pairs = tree.as_slice_pairs();
+                        assert_eq!(pairs.get(&150), Some(&"150".to_string()));
+                        pairs.as_slice().as_ptr() as usize
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        let cached = tree.as_slice_pairs().as_slice().as_ptr() as usize;
+        assert!(slices.iter().all(|&ptr| ptr == cached));
+    }
+
+    // 比较时计数的键，用于统计范围扫描中键的比较次数
+    static COMPARISONS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Clone, PartialEq, Eq)]
+    struct Counted(i32);
+
+    impl Ord for Counted {
+        fn cmp(&self, other: &Self) -> Ordering {
+            COMPARISONS.fetch_add(1, AtomicOrdering::Relaxed);
+            self.0.cmp(&other.0)
+        }
+    }
+
+    impl PartialOrd for Counted {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    #[test]
+    fn range_pair_iter_linear_scan() {
+        let n = 100000;
+        let tree: AVLTree<Counted, i32> = (0..n).map(|i| (Counted(i), i)).collect();
+
+        COMPARISONS.store(0, AtomicOrdering::Relaxed);
+        let count = tree.range(..).count();
+        assert_eq!(count, n as usize);
+        assert!(COMPARISONS.load(AtomicOrdering::Relaxed) <= 2 * n as usize);
+
+        COMPARISONS.store(0, AtomicOrdering::Relaxed);
+        let count = tree.range(Counted(10)..Counted(n - 10)).count();
+        assert_eq!(count, n as usize - 20);
+        assert!(COMPARISONS.load(AtomicOrdering::Relaxed) <= 2 * n as usize);
+
+        COMPARISONS.store(0, AtomicOrdering::Relaxed);
+        let count = tree.range(..).rev().count();
+        assert_eq!(count, n as usize);
+        assert!(COMPARISONS.load(AtomicOrdering::Relaxed) <= 2 * n as usize);
+    }
+
+    #[test]
+    fn min_max_pair_track_updates() {
+        let mut tree = AVLTree::new();
+        let mut reference = std::collections::BTreeMap::new();
+        for (i, key) in random_keys(2000, 7).into_iter().enumerate() {
+            if i % 3 == 2 {
+                let smallest = *reference.keys().next().unwrap();
+                tree.delete(smallest);
+                reference.remove(&smallest);
+                let largest = reference.keys().next_back().copied();
+                if let Some(largest) = largest.filter(|_| i % 2 == 0) {
+                    tree.delete(largest);
+                    reference.remove(&largest);
+                }
+            } else {
+                tree.insert(key, i);
+                reference.insert(key, i);
+            }
+            assert_eq!(tree.min_pair(), reference.iter().next());
+            assert_eq!(tree.max_pair(), reference.iter().next_back());
+        }
+        let cloned = tree.clone();
+        tree.delete(*cloned.min_pair().unwrap().0);
+        assert_eq!(cloned.min_pair(), reference.iter().next());
+        while let Some((&key, _)) = reference.iter().next() {
+            tree.delete(key);
+            reference.remove(&key);
+            assert_eq!(tree.min_pair(), reference.iter().next());
+            assert_eq!(tree.max_pair(), reference.iter().next_back());
+        }
+        assert_eq!(tree.min_pair(), None);
+    }
+
+    #[test]
+    fn min_max_pair_cache_follows_tree() {
+        fn moved<T>(value: T) -> T {
+            value
+        }
+        let mut tree: AVLTree<i32, i32> = (0..64).map(|key| (key, key)).collect();
+        assert_eq!(tree.min_pair(), Some((&0, &0)));
+        assert_eq!(tree.max_pair(), Some((&63, &63)));
+        // 移动树只移动根节点的指针，缓存的节点位置仍然有效
+        let mut trees = vec![moved(tree)];
+        assert_eq!(trees[0].min_pair(), Some((&0, &0)));
+        let boxed = Box::new(trees.pop().unwrap());
+        assert_eq!(boxed.max_pair(), Some((&63, &63)));
+        tree = *boxed;
+        // 通过可变借用修改最小、最大的值后读到新值
+        *tree.first_key_value_mut().unwrap().1 = -1;
+        assert_eq!(tree.min_pair(), Some((&0, &-1)));
+        *tree.get_mut(&63).unwrap() = -63;
+        assert_eq!(tree.max_pair(), Some((&63, &-63)));
+        // 拆分和合并之后两棵树各自缓存自己的节点
+        let mut upper = tree.split_off(&32);
+        assert_eq!(tree.max_pair(), Some((&31, &31)));
+        assert_eq!(upper.min_pair(), Some((&32, &32)));
+        tree.append(&mut upper);
+        assert_eq!(tree.max_pair(), Some((&63, &-63)));
+        assert_eq!(upper.min_pair(), None);
+        let cloned = tree.clone();
+        tree.pop_min();
+        assert_eq!(cloned.min_pair(), Some((&0, &-1)));
+        assert_eq!(tree.min_pair(), Some((&1, &1)));
+    }
+
+    #[test]
+    fn traverse_iter_len() {
+        let mut tree = AVLTree::new();
+        let keys = random_keys(100, 3);
+        for &key in &keys {
+            tree.insert(key, ());
+        }
+        let mut iter = tree.inorder_iter();
+        assert_eq!(iter.len(), tree.iter().count());
+        assert_eq!(iter.size_hint(), (iter.len(), Some(iter.len())));
+        iter.next();
+        assert_eq!(iter.len(), tree.iter().count() - 1);
+        assert_eq!(tree.levelorder_iter().len(), tree.iter().count());
+        assert_eq!(AVLTree::<i32, ()>::new().preorder_iter().len(), 0);
+    }
+
+    #[test]
+    fn pop_min_max() {
+        let mut tree = AVLTree::new();
+        let keys = random_keys(500, 11);
+        for &key in &keys {
+            tree.insert(key, -key);
+        }
+        let mut expected: Vec<i32> = tree.keys().copied().collect();
+        let mut popped = Vec::new();
+        while let Some((key, value)) = tree.pop_min() {
+            assert_eq!(value, -key);
+            popped.push(key);
+            assert!(tree.is_avl_tree());
+        }
+        assert_eq!(popped, expected);
+
+        for &key in &keys {
+            tree.insert(key, -key);
+        }
+        expected.reverse();
+        let mut popped = Vec::new();
+        while let Some((key, _)) = tree.pop_max() {
+            popped.push(key);
+            assert!(tree.is_avl_tree());
+            assert_eq!(tree.max_pair(), tree.iter().last());
+        }
+        assert_eq!(popped, expected);
+        assert_eq!(tree.pop_max(), None);
+    }
+
+    #[test]
+    fn split_off() {
+        let keys = random_keys(1000, 5);
+        let original: AVLTree<i32, i32> = keys.iter().map(|&key| (key, -key)).collect();
+        let sorted: Vec<i32> = original.keys().copied().collect();
+        for &pivot in &[i32::MIN, sorted[0], sorted[1] + 1, sorted[500], 0, i32::MAX] {
+            let mut lower = original.clone();
+            let upper = lower.split_off(&pivot);
+            for half in &[&lower, &upper] {
+                assert!(half.is_avl_tree());
+                assert!(half.is_bst());
+            }
+            assert!(lower.keys().all(|&key| key < pivot));
+            assert!(upper.keys().all(|&key| key >= pivot));
+            let union: Vec<(i32, i32)> = lower
+                .iter()
+                .chain(upper.iter())
+                .map(|(&key, &value)| (key, value))
+                .collect();
+            let expected: Vec<(i32, i32)> =
+                original.iter().map(|(&key, &value)| (key, value)).collect();
+            assert_eq!(union, expected);
+            assert_eq!(lower.min_pair(), lower.iter().next());
+            assert_eq!(upper.max_pair(), upper.iter().last());
+            for (i, (key, _)) in upper.iter().enumerate() {
+                assert_eq!(upper.rank(key), i);
+            }
+        }
+    }
+
+    #[test]
+    fn append() {
+        let mut tree: AVLTree<i32, i32> = (0..100).map(|key| (key, 0)).collect();
+        let mut other: AVLTree<i32, i32> = (50..300).map(|key| (key, 1)).collect();
+        tree.append(&mut other);
+        assert!(other.is_empty());
+        assert!(tree.is_avl_tree());
+        assert_eq!(
+            tree.keys().copied().collect::<Vec<_>>(),
+            (0..300).collect::<Vec<_>>()
+        );
+        assert!(tree
+            .iter()
+            .all(|(&key, &value)| value == (key >= 50) as i32));
+
+        // 键区间不相交时直接连接
+        let mut low: AVLTree<i32, i32> = (-1000..-990).map(|key| (key, 2)).collect();
+        low.append(&mut tree);
+        assert!(tree.is_empty());
+        assert!(low.is_avl_tree());
+        assert_eq!(low.iter().count(), 310);
+        let mut high: AVLTree<i32, i32> = (1000..2000).map(|key| (key, 3)).collect();
+        high.append(&mut low);
+        assert!(high.is_avl_tree());
+        assert_eq!(high.min_pair(), Some((&-1000, &2)));
+        assert_eq!(high.max_pair(), Some((&1999, &3)));
+        assert_eq!(high.iter().count(), 1310);
+        for (i, (key, _)) in high.iter().enumerate() {
+            assert_eq!(high.rank(key), i);
+        }
+
+        let mut empty = AVLTree::new();
+        empty.append(&mut high);
+        assert_eq!(empty.iter().count(), 1310);
+        empty.append(&mut high);
+        assert_eq!(empty.iter().count(), 1310);
+    }
+
+    #[test]
+    fn retain() {
+        let mut tree: AVLTree<i32, i32> = (0..100).map(|key| (key, key * 2)).collect();
+        tree.retain(|&key, _| key % 2 == 0);
+        assert!(tree.is_avl_tree());
+        let expected: Vec<(i32, i32)> = (0..100).step_by(2).map(|key| (key, key * 2)).collect();
+        assert_eq!(
+            tree.iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(tree.min_pair(), Some((&0, &0)));
+        assert_eq!(tree.max_pair(), Some((&98, &196)));
+        tree.retain(|_, &value| value > 1000);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn extract_if() {
+        let mut tree: AVLTree<i32, char> = (0..20).map(|key| (key, 'x')).collect();
+        let removed = tree.extract_if(|&key, _| key % 2 == 1);
+        let odd: Vec<(i32, char)> = (1..20).step_by(2).map(|key| (key, 'x')).collect();
+        assert_eq!(removed, odd);
+        assert!(tree.is_avl_tree());
+        assert_eq!(
+            tree.keys().copied().collect::<Vec<_>>(),
+            (0..20).step_by(2).collect::<Vec<_>>()
+        );
+        assert!(tree.extract_if(|_, _| false).is_empty());
+        assert_eq!(tree.extract_if(|_, _| true).len(), 10);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn floor_ceiling() {
+        let tree: AVLTree<i32, i32> = (0..50).map(|key| (key * 10, key)).collect();
+        for key in 0..50 {
+            // 键存在时返回其本身
+            assert_eq!(tree.floor(&(key * 10)), Some((&(key * 10), &key)));
+            assert_eq!(tree.ceiling(&(key * 10)), Some((&(key * 10), &key)));
+            // 键位于两个相邻键之间
+            assert_eq!(tree.floor(&(key * 10 + 5)), Some((&(key * 10), &key)));
+            assert_eq!(tree.ceiling(&(key * 10 - 5)), Some((&(key * 10), &key)));
+        }
+        // 超出键的范围
+        assert_eq!(tree.floor(&-1), None);
+        assert_eq!(tree.ceiling(&491), None);
+        assert_eq!(tree.floor(&1000), Some((&490, &49)));
+        assert_eq!(tree.ceiling(&-1000), Some((&0, &0)));
+        assert_eq!(AVLTree::<i32, i32>::new().floor(&0), None);
+    }
+
+    #[test]
+    fn empty_tree_is_avl_tree() {
+        assert!(AVLTree::<i32, i32>::new().is_avl_tree());
+        let mut tree = AVLTree::new();
+        tree.insert(1, 1);
+        tree.delete(1);
+        assert!(tree.is_avl_tree());
+    }
+
+    #[test]
+    fn ord_keys() {
+        let mut tree = AVLTree::new();
+        for key in random_keys(1000, 13) {
+            tree.insert(key, ());
+        }
+        assert!(tree.is_avl_tree());
+        assert!(tree.is_bst());
+        let mut words = AVLTree::new();
+        for word in "the quick brown fox jumps over the lazy dog".split(' ') {
+            words.insert(word, word.len());
+        }
+        assert_eq!(words.min_pair(), Some((&"brown", &5)));
+        assert_eq!(words.iter().count(), 8);
+    }
+
+    #[test]
+    fn insert_million_keys() {
+        let mut tree = AVLTree::new();
+        for key in random_keys(1_000_000, 17) {
+            tree.insert(key, ());
+        }
+        for key in 0..100_000 {
+            tree.insert(key, ());
+        }
+        assert!(tree.is_avl_tree());
+        assert!(tree.is_bst());
+        assert!(tree.height() <= 30);
+    }
+
+    #[test]
+    fn lazy_traverse_iters() {
+        use std::collections::HashMap;
+
+        // 由邻接表递归计算出各种遍历顺序，作为对照
+        fn walk(
+            key: Option<i32>,
+            children: &HashMap<i32, (Option<i32>, Option<i32>)>,
+            pre: &mut Vec<i32>,
+            inorder: &mut Vec<i32>,
+            post: &mut Vec<i32>,
+        ) {
+            if let Some(key) = key {
+                let (left, right) = children[&key];
+                pre.push(key);
+                walk(left, children, pre, inorder, post);
+                inorder.push(key);
+                walk(right, children, pre, inorder, post);
+                post.push(key);
+            }
+        }
+
+        for n in [0, 1, 2, 3, 10, 100, 1000] {
+            let mut tree = AVLTree::new();
+            for key in random_keys(n, 23) {
+                tree.insert(key, -key);
+            }
+            let adjacency = tree.to_adjacency_list();
+            let children: HashMap<i32, (Option<i32>, Option<i32>)> = adjacency
+                .iter()
+                .map(|&(key, left, right)| (key, (left, right)))
+                .collect();
+            let root = adjacency.first().map(|&(key, _, _)| key);
+            let (mut pre, mut inorder, mut post) = (Vec::new(), Vec::new(), Vec::new());
+            walk(root, &children, &mut pre, &mut inorder, &mut post);
+            let mut level = Vec::new();
+            let mut queue: std::collections::VecDeque<i32> = root.into_iter().collect();
+            while let Some(key) = queue.pop_front() {
+                level.push(key);
+                let (left, right) = children[&key];
+                queue.extend(left);
+                queue.extend(right);
+            }
+
+            let keys = |iter: &mut dyn Iterator<Item = (&i32, &i32)>| -> Vec<i32> {
+                iter.map(|(&key, &value)| {
+                    assert_eq!(value, -key);
+                    key
+                })
+                .collect()
+            };
+            assert_eq!(keys(&mut tree.preorder_iter()), pre);
+            assert_eq!(keys(&mut tree.inorder_iter()), inorder);
+            assert_eq!(keys(&mut tree.postorder_iter()), post);
+            assert_eq!(keys(&mut tree.levelorder_iter()), level);
+            assert_eq!(tree.postorder_iter().len(), tree.iter().count());
+        }
+    }
+
+    #[test]
+    fn count_range() {
+        let mut tree = AVLTree::new();
+        for key in random_keys(300, 29) {
+            tree.insert(key % 1000, ());
+        }
+        let probes = random_keys(200, 31);
+        for pair in probes.chunks(2) {
+            let (lo, hi) = (pair[0] % 1100 - 50, pair[1] % 1100 - 50);
+            let bounds = [
+                (Bound::Included(lo), Bound::Included(hi)),
+                (Bound::Included(lo), Bound::Excluded(hi)),
+                (Bound::Excluded(lo), Bound::Included(hi)),
+                (Bound::Excluded(lo), Bound::Excluded(hi)),
+                (Bound::Unbounded, Bound::Included(hi)),
+                (Bound::Excluded(lo), Bound::Unbounded),
+            ];
+            for range in bounds {
+                let expected = tree
+                    .keys()
+                    .filter(|key| std::ops::RangeBounds::contains(&range, *key))
+                    .count();
+                assert_eq!(tree.count_range(range), expected);
+                if lo < hi {
+                    assert_eq!(tree.range(range).collect::<Vec<_>>().len(), expected);
+                }
+            }
+        }
+        assert_eq!(tree.count_range(..), tree.iter().count());
+    }
+
+    #[test]
+    fn map_values() {
+        let mut tree = AVLTree::new();
+        for (i, key) in random_keys(200, 37).into_iter().enumerate() {
+            tree.insert(key, char::from(b'a' + (i % 26) as u8));
+        }
+        let keys: Vec<i32> = tree.keys().copied().collect();
+        let chars: Vec<char> = tree.values().copied().collect();
+        let adjacency = tree.to_adjacency_list();
+        let height = tree.height();
+
+        let mut visited = Vec::new();
+        let codes = tree.map_values(|&c| {
+            visited.push(c);
+            c as u32
+        });
+        assert_eq!(visited, chars);
+        assert_eq!(codes.keys().copied().collect::<Vec<_>>(), keys);
+        assert_eq!(
+            codes
+                .values()
+                .map(|&code| char::from_u32(code).unwrap())
+                .collect::<Vec<_>>(),
+            chars
+        );
+        assert_eq!(codes.to_adjacency_list(), adjacency);
+        assert_eq!(codes.height(), height);
+        assert!(codes.is_avl_tree());
+        assert_eq!(codes.select(10).map(|(&k, _)| k), Some(keys[10]));
+        assert_eq!(codes.min_pair().map(|(&k, _)| k), keys.first().copied());
+    }
+
+    #[test]
+    fn index() {
+        let tree: AVLTree<i32, i32> = (0..10).map(|key| (key, key * key)).collect();
+        for key in 0..10 {
+            assert_eq!(tree[&key], key * key);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "key not found")]
+    fn index_missing_key() {
+        let tree: AVLTree<i32, i32> = (0..10).map(|key| (key, key)).collect();
+        let _ = tree[&10];
+    }
+
+    #[test]
+    fn get_or_insert_with() {
+        let mut tree = AVLTree::new();
+        let mut calls = 0;
+        for key in 0..10 {
+            tree.insert(key, vec![key]);
+        }
+        tree.get_or_insert_with(3, || {
+            calls += 1;
+            Vec::new()
+        })
+        .unwrap()
+        .push(30);
+        assert_eq!(calls, 0);
+        assert_eq!(tree.get(&3), Some(&vec![3, 30]));
+
+        tree.get_or_insert_with(20, || {
+            calls += 1;
+            vec![2]
+        })
+        .unwrap()
+        .push(20);
+        assert_eq!(calls, 1);
+        assert_eq!(tree.get(&20), Some(&vec![2, 20]));
+        assert!(tree.is_avl_tree());
+    }
+
+    #[test]
+    fn retain_range() {
+        let full: AVLTree<i32, i32> = (0..100).map(|key| (key, -key)).collect();
+        let mut tree = full.clone();
+        tree.retain_range(20..=40);
+        assert!(tree.is_avl_tree());
+        assert_eq!(
+            tree.keys().copied().collect::<Vec<_>>(),
+            (20..=40).collect::<Vec<_>>()
+        );
+        assert_eq!(tree.get(&40), Some(&-40));
+        assert_eq!(tree.min_pair(), Some((&20, &-20)));
+        assert_eq!(tree.max_pair(), Some((&40, &-40)));
+
+        let bounds = [
+            (Bound::Excluded(20), Bound::Excluded(40)),
+            (Bound::Unbounded, Bound::Included(10)),
+            (Bound::Excluded(90), Bound::Unbounded),
+            (Bound::Included(-5), Bound::Included(200)),
+            (Bound::Included(50), Bound::Excluded(50)),
+        ];
+        for range in bounds {
+            let mut tree = full.clone();
+            tree.retain_range(range);
+            assert!(tree.is_avl_tree());
+            let expected: Vec<i32> = (0..100)
+                .filter(|key| std::ops::RangeBounds::contains(&range, key))
+                .collect();
+            assert_eq!(tree.keys().copied().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn balance_factor() {
+        let mut tree = AVLTree::new();
+        let keys = random_keys(2000, 41);
+        for (i, &key) in keys.iter().enumerate() {
+            tree.insert(key % 500, i);
+            if i % 3 == 0 {
+                tree.delete(keys[i / 2] % 500);
+            }
+        }
+        let present: Vec<i32> = tree.keys().copied().collect();
+        for key in &present {
+            let balance = tree.balance_factor(key).unwrap();
+            assert!((-1..=1).contains(&balance));
+        }
+        assert_eq!(tree.balance_factor(&1000), None);
+    }
+
+    #[test]
+    fn iter_rev() {
+        for n in [0, 1, 2, 7, 500] {
+            let mut tree = AVLTree::new();
+            for key in random_keys(n, 43) {
+                tree.insert(key, -key);
+            }
+            let mut forward: Vec<(&i32, &i32)> = tree.iter().collect();
+            forward.reverse();
+            assert_eq!(tree.iter_rev().collect::<Vec<_>>(), forward);
+        }
+    }
+
+    #[test]
+    fn new_by_descending() {
+        let mut tree = AVLTree::new_by(|a: &i32, b: &i32| b.cmp(a));
+        let keys = random_keys(1000, 47);
+        for &key in &keys {
+            tree.insert(key % 300, -key);
+        }
+        assert!(tree.is_avl_tree());
+        let mut expected: Vec<i32> = keys.iter().map(|key| key % 300).collect();
+        expected.sort_unstable_by(|a, b| b.cmp(a));
+        expected.dedup();
+        assert_eq!(tree.keys().copied().collect::<Vec<_>>(), expected);
+        assert_eq!(tree.min_pair().map(|(k, _)| *k), expected.first().copied());
+
+        let middle = expected[expected.len() / 2];
+        assert!(tree.get(&middle).is_some());
+        let above = tree.successor(&middle).map(|(k, _)| *k);
+        assert_eq!(above, expected.get(expected.len() / 2 + 1).copied());
+        let below = tree.predecessor(&middle).map(|(k, _)| *k);
+        assert_eq!(below, expected.get(expected.len() / 2 - 1).copied());
+
+        let ranged: Vec<i32> = tree
+            .range((Bound::Included(200), Bound::Excluded(100)))
+            .map(|(k, _)| *k)
+            .collect();
+        let filtered: Vec<i32> = expected
+            .iter()
+            .copied()
+            .filter(|&key| key <= 200 && key > 100)
+            .collect();
+        assert_eq!(ranged, filtered);
+
+        tree.delete(middle);
+        assert!(!tree.contains(&middle));
+        tree.retain(|key, _| key % 2 == 0);
+        assert!(tree.is_avl_tree());
+        let evens: Vec<i32> = expected
+            .iter()
+            .copied()
+            .filter(|&key| key != middle && key % 2 == 0)
+            .collect();
+        assert_eq!(tree.keys().copied().collect::<Vec<_>>(), evens);
+        tree.insert(1, 0);
+        assert!(tree.is_avl_tree());
+        let mut with_one = evens;
+        with_one.push(1);
+        with_one.sort_unstable_by(|a, b| b.cmp(a));
+        assert_eq!(tree.keys().copied().collect::<Vec<_>>(), with_one);
+    }
+
+    #[test]
+    fn range_mut() {
+        let mut tree = AVLTree::new();
+        for key in 0..10 {
+            tree.insert(key, key * 10);
+        }
+        for (_, value) in tree.range_mut(3..7) {
+            *value = -*value;
+        }
+        for key in 0..10 {
+            let expected = if (3..7).contains(&key) {
+                -key * 10
+            } else {
+                key * 10
+            };
+            assert_eq!(tree.get(&key), Some(&expected));
+        }
+
+        let mut tree = AVLTree::new();
+        let keys = random_keys(500, 53);
+        for &key in &keys {
+            tree.insert(key % 200, 0);
+        }
+        let bounds = (Bound::Excluded(50), Bound::Included(120));
+        let visited: Vec<i32> = tree
+            .range_mut(bounds)
+            .map(|(k, v)| {
+                *v += 1;
+                *k
+            })
+            .collect();
+        let expected: Vec<i32> = tree.range(bounds).map(|(k, _)| *k).collect();
+        assert_eq!(visited, expected);
+        assert!(tree
+            .iter()
+            .all(|(k, v)| *v == i32::from(expected.contains(k))));
+        assert_eq!(
+            tree.range_mut((Bound::Excluded(150), Bound::Excluded(150)))
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn remove_range() {
+        let ranges = [
+            (Bound::Included(20), Bound::Included(60)),
+            (Bound::Excluded(20), Bound::Excluded(60)),
+            (Bound::Included(20), Bound::Excluded(60)),
+            (Bound::Excluded(20), Bound::Included(60)),
+            (Bound::Unbounded, Bound::Included(35)),
+            (Bound::Excluded(35), Bound::Unbounded),
+            (Bound::Unbounded, Bound::Unbounded),
+            (Bound::Included(200), Bound::Unbounded),
+        ];
+        for range in ranges {
+            let mut tree = AVLTree::new();
+            for key in random_keys(300, 59) {
+                tree.insert(key % 100, key);
+            }
+            let before: Vec<i32> = tree.keys().copied().collect();
+            let removed = tree.remove_range(range);
+            assert!(tree.is_avl_tree());
+            let survivors: Vec<i32> = before
+                .iter()
+                .copied()
+                .filter(|key| !std::ops::RangeBounds::contains(&range, key))
+                .collect();
+            assert_eq!(removed, before.len() - survivors.len());
+            assert_eq!(tree.keys().copied().collect::<Vec<_>>(), survivors);
+        }
+    }
+
+    #[test]
+    fn set_operations_on_keys() {
+        let mut a = AVLTree::new();
+        let mut b = AVLTree::new();
+        for key in random_keys(300, 61) {
+            a.insert(key % 200, 'a');
+        }
+        for key in random_keys(300, 67) {
+            b.insert(key % 200 + 100, 'b');
+        }
+        let left: Vec<i32> = a.keys().copied().collect();
+        let right: Vec<i32> = b.keys().copied().collect();
+
+        let mut union: Vec<i32> = left.iter().chain(right.iter()).copied().collect();
+        union.sort_unstable();
+        union.dedup();
+        let intersection: Vec<i32> = left
+            .iter()
+            .copied()
+            .filter(|key| right.contains(key))
+            .collect();
+        let difference: Vec<i32> = left
+            .iter()
+            .copied()
+            .filter(|key| !right.contains(key))
+            .collect();
+        assert!(!intersection.is_empty());
+
+        assert_eq!(a.union_keys(&b).copied().collect::<Vec<_>>(), union);
+        assert_eq!(
+            a.intersection_keys(&b).copied().collect::<Vec<_>>(),
+            intersection
+        );
+        assert_eq!(
+            a.difference_keys(&b).copied().collect::<Vec<_>>(),
+            difference
+        );
+
+        let empty = AVLTree::new();
+        assert_eq!(a.union_keys(&empty).count(), left.len());
+        assert_eq!(a.intersection_keys(&empty).count(), 0);
+        assert_eq!(empty.difference_keys(&a).count(), 0);
+    }
+
+    #[test]
+    fn merge_with_sums_collisions() {
+        let mut a = AVLTree::new();
+        let mut b = AVLTree::new();
+        let mut expected = std::collections::BTreeMap::new();
+        for key in random_keys(400, 71) {
+            let key = key % 150;
+            *a.entry(key).unwrap().or_insert(0) += 1;
+            *expected.entry(key).or_insert(0) += 1;
+        }
+        for key in random_keys(400, 73) {
+            let key = key % 150 + 75;
+            *b.entry(key).unwrap().or_insert(0) += 1;
+            *expected.entry(key).or_insert(0) += 1;
+        }
+        a.merge_with(b, |_, mine, theirs| mine + theirs);
+        assert!(a.is_avl_tree());
+        let merged: Vec<(i32, i32)> = a.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(merged, expected.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn nearest() {
+        let mut tree = AVLTree::new();
+        for key in random_keys(300, 79) {
+            tree.insert(key % 10_000, ());
+        }
+        let keys: Vec<i32> = tree.keys().copied().collect();
+        let distance = |a: &i32, b: &i32| (a - b).abs();
+        let (mut closer_below, mut closer_above) = (0, 0);
+        for query in -100..10_100 {
+            let (found, _) = tree.nearest(&query, distance).unwrap();
+            let best = keys.iter().map(|key| distance(&query, key)).min().unwrap();
+            let expected = keys
+                .iter()
+                .find(|key| distance(&query, key) == best)
+                .unwrap();
+            assert_eq!(found, expected);
+            match found.cmp(&query) {
+                Ordering::Less => closer_below += 1,
+                Ordering::Greater => closer_above += 1,
+                Ordering::Equal => {}
+            }
+        }
+        assert!(closer_below > 0 && closer_above > 0);
+        assert_eq!(AVLTree::<i32, ()>::new().nearest(&1, distance), None);
+    }
+
+    #[test]
+    fn first_and_last_key_value_mut() {
+        let mut tree = AVLTree::new();
+        assert!(tree.first_key_value_mut().is_none());
+        assert!(tree.last_key_value_mut().is_none());
+        let keys = random_keys(200, 83);
+        for &key in &keys {
+            tree.insert(key, 0);
+        }
+        let min = *keys.iter().min().unwrap();
+        let max = *keys.iter().max().unwrap();
+        for _ in 0..3 {
+            let (key, value) = tree.first_key_value_mut().unwrap();
+            assert_eq!(*key, min);
+            *value += 1;
+        }
+        *tree.last_key_value_mut().unwrap().1 -= 1;
+        assert_eq!(tree.min_pair(), Some((&min, &3)));
+        assert_eq!(tree.max_pair(), Some((&max, &-1)));
+    }
+
+    #[test]
+    fn drop_large_tree_on_small_stack() {
+        let tree: AVLTree<u32, u32> = (0..500_000).map(|key| (key, key)).collect();
+        let handle = std::thread::Builder::new()
+            .stack_size(32 * 1024)
+            .spawn(move || {
+                let tree = tree;
+                assert_eq!(tree.min_pair(), Some((&0, &0)));
+            })
+            .unwrap();
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn rebalance_minimizes_height() {
+        let mut tree = AVLTree::new();
+        let keys = random_keys(5000, 89);
+        for &key in &keys {
+            tree.insert(key, key);
+        }
+        for &key in keys.iter().step_by(3) {
+            tree.delete(key);
+        }
+        for key in 0..2000 {
+            tree.insert(key * 2, key);
+        }
+        let before = tree.height();
+        let pairs: Vec<(i32, i32)> = tree.iter().map(|(k, v)| (*k, *v)).collect();
+        tree.rebalance();
+        let n = pairs.len() as u32;
+        let minimal = 32 - n.leading_zeros();
+        assert_eq!(tree.height(), minimal);
+        assert!(tree.height() <= before);
+        assert!(tree.is_avl_tree());
+        assert_eq!(
+            tree.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+            pairs
+        );
+
+        let mut empty: AVLTree<i32, i32> = AVLTree::new();
+        empty.rebalance();
+        assert_eq!(empty.height(), 0);
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut tree = AVLTree::new();
+        for key in 0..100 {
+            tree.insert(key, key * 10);
+        }
+        if let Some([a, b]) = tree.get_many_mut([&3, &97]) {
+            std::mem::swap(a, b);
+        }
+        assert_eq!(tree.get(&3), Some(&970));
+        assert_eq!(tree.get(&97), Some(&30));
+
+        let [a, b, c] = tree.get_many_mut([&10, &20, &30]).unwrap();
+        *a += 1;
+        *b += 2;
+        *c += 3;
+        assert_eq!(tree.get(&10), Some(&101));
+        assert_eq!(tree.get(&20), Some(&202));
+        assert_eq!(tree.get(&30), Some(&303));
+
+        assert!(tree.get_many_mut([&5, &6, &5]).is_none());
+        assert!(tree.get_many_mut([&5, &500]).is_none());
+        assert!(tree.get_many_mut::<0>([]).is_some());
+    }
+
+    #[test]
+    fn get_many_mut_ancestor_keys() {
+        // 1为根节点，是2的祖先；先取祖先再取后代的值，两个可变借用都必须仍然有效
+        let mut tree: AVLTree<i32, i32> = (0..3).map(|key| (key, key)).collect();
+        let [root, leaf] = tree.get_many_mut([&1, &2]).unwrap();
+        *root += 10;
+        *leaf += 20;
+        let [leaf, root, other] = tree.get_many_mut([&2, &1, &0]).unwrap();
+        std::mem::swap(leaf, root);
+        *other -= 1;
+        assert_eq!(tree.values().copied().collect::<Vec<_>>(), vec![-1, 22, 11]);
+
+        let mut tree: AVLTree<i32, i32> = (0..31).map(|key| (key, key)).collect();
+        let keys: Vec<i32> = tree.keys().copied().collect();
+        let refs: [&i32; 31] = std::array::from_fn(|i| &keys[i]);
+        let values = tree.get_many_mut(refs).unwrap();
+        for value in values {
+            *value *= 2;
+        }
+        assert!(tree.iter().all(|(key, value)| *value == key * 2));
+    }
+
+    #[test]
+    fn hash_ignores_insert_order() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash_of = |tree: &AVLTree<i32, i32>| {
+            let mut hasher = DefaultHasher::new();
+            tree.hash(&mut hasher);
+            hasher.finish()
+        };
+        let keys = random_keys(300, 97);
+        let mut a = AVLTree::new();
+        for &key in &keys {
+            a.insert(key, -key);
+        }
+        let mut b = AVLTree::new();
+        for &key in keys.iter().rev() {
+            b.insert(key, -key);
+        }
+        assert_eq!(a, b);
+        assert_eq!(hash_of(&a), hash_of(&b));
+
+        b.insert(keys[0], 0);
+        assert_ne!(hash_of(&a), hash_of(&b));
+    }
+
+    #[test]
+    fn chunk_by_key() {
+        let tree: AVLTree<i32, i32> = (0..20).map(|key| (key, key * key)).collect();
+        let buckets: Vec<Vec<(&i32, &i32)>> = tree.chunk_by_key(5).collect();
+        assert_eq!(buckets.len(), 4);
+        for (i, bucket) in buckets.iter().enumerate() {
+            let keys: Vec<i32> = bucket.iter().map(|(k, _)| **k).collect();
+            let start = i as i32 * 5;
+            assert_eq!(keys, (start..start + 5).collect::<Vec<_>>());
+            assert!(bucket.iter().all(|(k, v)| **v == **k * **k));
+        }
+        assert_eq!(tree.chunk_by_key(0).count(), 20);
+        assert_eq!(AVLTree::<i32, i32>::new().chunk_by_key(5).count(), 0);
+
+        // 桶的终点超出i32的上限时不能溢出
+        let top: AVLTree<i32, ()> = (i32::MAX - 7..=i32::MAX).map(|key| (key, ())).collect();
+        let buckets: Vec<Vec<i32>> = top
+            .chunk_by_key(5)
+            .map(|bucket| bucket.into_iter().map(|(k, _)| *k).collect())
+            .collect();
+        assert_eq!(
+            buckets,
+            vec![
+                (i32::MAX - 7..=i32::MAX - 3).collect::<Vec<_>>(),
+                (i32::MAX - 2..=i32::MAX).collect::<Vec<_>>()
+            ]
+        );
+
+        // 降序树按降序划分桶，距离仍按数值计算
+        let mut descending = AVLTree::new_by(|a: &i32, b: &i32| b.cmp(a));
+        descending.extend(vec![(1, ()), (3, ()), (6, ()), (12, ()), (-20, ())]);
+        let buckets: Vec<Vec<i32>> = descending
+            .chunk_by_key(5)
+            .map(|bucket| bucket.into_iter().map(|(k, _)| *k).collect())
+            .collect();
+        assert_eq!(buckets, vec![vec![12], vec![6, 3], vec![1], vec![-20]]);
+    }
+
+    #[test]
+    fn get_pairs_matches_get_pair() {
+        let mut tree = AVLTree::new();
+        for key in random_keys(500, 101) {
+            tree.insert(key % 1000, key);
+        }
+        let sorted: Vec<i32> = (-10..1010).collect();
+        let expected: Vec<Option<(&i32, &i32)>> = sorted.iter().map(|k| tree.get_pair(k)).collect();
+        assert_eq!(tree.get_pairs(&sorted), expected);
+
+        let mut shuffled = random_keys(300, 103);
+        for key in shuffled.iter_mut() {
+            *key %= 1000;
+        }
+        shuffled.extend([5, 5, 3, 999, 0]);
+        let expected: Vec<Option<(&i32, &i32)>> =
+            shuffled.iter().map(|k| tree.get_pair(k)).collect();
+        assert_eq!(tree.get_pairs(&shuffled), expected);
+        assert!(AVLTree::<i32, i32>::new()
+            .get_pairs(&[1, 2])
+            .iter()
+            .all(Option::is_none));
+    }
+
+    #[test]
+    fn successor_and_predecessor_or_equal() {
+        let tree: AVLTree<i32, i32> = (0..50).map(|key| (key * 2, key)).collect();
+        for key in 0..100 {
+            if key % 2 == 0 {
+                assert_eq!(tree.successor_or_equal(&key), tree.get_pair(&key));
+                assert_eq!(tree.predecessor_or_equal(&key), tree.get_pair(&key));
+                assert_ne!(tree.successor(&key), tree.get_pair(&key));
+                assert_ne!(tree.predecessor(&key), tree.get_pair(&key));
+            } else {
+                assert_eq!(tree.successor_or_equal(&key), tree.successor(&key));
+                assert_eq!(tree.predecessor_or_equal(&key), tree.predecessor(&key));
+            }
+        }
+        assert_eq!(tree.successor_or_equal(&99), None);
+        assert_eq!(tree.predecessor_or_equal(&-1), None);
+    }
+
+    #[test]
+    fn into_keys_and_values() {
+        let keys = random_keys(1000, 107);
+        let mut tree = AVLTree::new();
+        for &key in &keys {
+            tree.insert(key, key.to_string());
+        }
+        let copy = tree.clone();
+        let owned: Vec<i32> = tree.into_keys().collect();
+        assert!(owned.windows(2).all(|pair| pair[0] < pair[1]));
+        let mut expected = keys;
+        expected.sort_unstable();
+        expected.dedup();
+        assert_eq!(owned, expected);
+
+        let values: Vec<String> = copy.into_values().collect();
+        let strings: Vec<String> = expected.iter().map(|key| key.to_string()).collect();
+        assert_eq!(values, strings);
+    }
+
+    #[test]
+    fn memory_usage_is_linear() {
+        let empty = AVLTree::<u64, u64>::new().memory_usage();
+        let mut per_node = None;
+        for n in [1u64, 10, 100, 1000, 10_000] {
+            let tree: AVLTree<u64, u64> = (0..n).map(|key| (key, key)).collect();
+            assert_eq!(tree.len() as u64, n);
+            let grown = tree.memory_usage() - empty;
+            assert_eq!(grown % tree.len(), 0);
+            let size = grown / tree.len();
+            assert!(size >= std::mem::size_of::<(u64, u64)>());
+            assert_eq!(*per_node.get_or_insert(size), size);
+        }
+    }
+
+    #[test]
+    fn replace_key() {
+        let mut tree: AVLTree<i32, i32> = (0..100).map(|key| (key * 2, key)).collect();
+        for key in 0..50 {
+            assert_eq!(tree.replace_key(&(key * 2), key * 2 + 1), Ok(()));
+            assert!(tree.is_avl_tree());
+        }
+        for key in 0..50 {
+            assert_eq!(tree.get(&(key * 2 + 1)), Some(&key));
+            assert!(!tree.contains(&(key * 2)));
+        }
+        assert_eq!(tree.len(), 100);
+
+        assert_eq!(tree.replace_key(&1, 3), Err(0));
+        assert!(!tree.contains(&1));
+        assert_eq!(tree.get(&3), Some(&1));
+        assert_eq!(tree.len(), 99);
+
+        assert_eq!(tree.replace_key(&1000, 1), Ok(()));
+        assert!(!tree.contains(&1));
+        assert_eq!(tree.replace_key(&3, 3), Ok(()));
+        assert_eq!(tree.get(&3), Some(&1));
+        assert!(tree.is_avl_tree());
+    }
+
+    #[test]
+    fn retain_rebuild_matches_deletes() {
+        for keep_every in [1, 2, 10, 1000, 50_000] {
+            let keys = random_keys(20_000, 109);
+            let mut rebuilt = AVLTree::new();
+            for &key in &keys {
+                rebuilt.insert(key, -key);
+            }
+            let mut deleted = rebuilt.clone();
+            for &key in &keys {
+                if key % keep_every != 0 {
+                    deleted.delete(key);
+                }
+            }
+            rebuilt.retain(|key, _| key % keep_every == 0);
+            assert!(rebuilt.is_avl_tree());
+            assert_eq!(rebuilt.len(), deleted.len());
+            assert_eq!(rebuilt, deleted);
+        }
+    }
+
+    #[test]
+    fn partition_point() {
+        let mut tree = AVLTree::new();
+        for key in random_keys(400, 113) {
+            tree.insert(key % 1000, key);
+        }
+        let keys: Vec<i32> = tree.keys().copied().collect();
+        for threshold in -5..1005 {
+            let found = tree.partition_point(|&key| key < threshold);
+            assert_eq!(found, tree.ceiling(&threshold));
+            let index = keys.partition_point(|&key| key < threshold);
+            assert_eq!(found.map(|(k, _)| *k), keys.get(index).copied());
+        }
+        assert_eq!(tree.partition_point(|_| true), None);
+        assert_eq!(tree.partition_point(|_| false), tree.min_pair());
+        assert_eq!(AVLTree::<i32, i32>::new().partition_point(|_| false), None);
+    }
+
+    #[test]
+    fn sum_range() {
+        let mut tree = AVLTree::new();
+        for key in random_keys(500, 127) {
+            tree.insert(i64::from(key % 1000), i64::from(key));
+        }
+        let ranges = [
+            (Bound::Included(100), Bound::Excluded(600)),
+            (Bound::Excluded(100), Bound::Included(600)),
+            (Bound::Unbounded, Bound::Included(250)),
+            (Bound::Included(900), Bound::Unbounded),
+            (Bound::Unbounded, Bound::Unbounded),
+        ];
+        for range in ranges {
+            let expected: i64 = tree.range(range).map(|(_, v)| v).sum();
+            assert_eq!(tree.sum_range(range), Some(expected));
+        }
+        assert_eq!(tree.sum_range(2000..), None);
+        let max = tree.fold_range(.., |acc, value| acc.max(*value));
+        assert_eq!(max, tree.values().max().copied());
+    }
+
+    #[test]
+    fn sum_tree() {
+        // 以AVLTree逐个相加的结果为准
+        fn check(tree: &SumTree<i64, i64>, expected: &AVLTree<i64, i64>) {
+            let ranges = [
+                (Bound::Included(100), Bound::Excluded(600)),
+                (Bound::Excluded(333), Bound::Included(334)),
+                (Bound::Unbounded, Bound::Included(250)),
+                (Bound::Included(900), Bound::Unbounded),
+                (Bound::Unbounded, Bound::Unbounded),
+            ];
+            assert_eq!(tree.len(), expected.len());
+            for range in ranges {
+                assert_eq!(tree.sum_range(range), expected.sum_range(range));
+            }
+        }
+        let mut tree = SumTree::new();
+        let mut expected = AVLTree::new();
+        for key in random_keys(500, 31) {
+            tree.insert(i64::from(key % 1000), i64::from(key % 97));
+            expected.insert(i64::from(key % 1000), i64::from(key % 97));
+        }
+        check(&tree, &expected);
+        for key in random_keys(200, 32) {
+            tree.insert(i64::from(key % 1000), 1);
+            expected.insert(i64::from(key % 1000), 1);
+        }
+        check(&tree, &expected);
+        for key in random_keys(200, 33) {
+            tree.delete(i64::from(key % 1000));
+            expected.delete(i64::from(key % 1000));
+        }
+        check(&tree, &expected);
+        for key in random_keys(100, 34) {
+            let key = i64::from(key % 1000);
+            let found = tree.update(&key, |value| *value += 1000);
+            assert_eq!(found, expected.contains(&key));
+            if let Some(value) = expected.get_mut(&key) {
+                *value += 1000;
+            }
+        }
+        check(&tree, &expected);
+        assert_eq!(SumTree::<i64, i64>::new().sum_range(..), None);
+    }
+
+    #[test]
+    fn sum_tree_reads_subtree_sums() {
+        static ADDS: AtomicUsize = AtomicUsize::new(0);
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct Counted(u64);
+        impl std::ops::Add for Counted {
+            type Output = Counted;
+            fn add(self, other: Counted) -> Counted {
+                ADDS.fetch_add(1, AtomicOrdering::Relaxed);
+                Counted(self.0 + other.0)
+            }
+        }
+        let mut tree: SumTree<u64, Counted> = (0..4096).map(|key| (key, Counted(key))).collect();
+        ADDS.store(0, AtomicOrdering::Relaxed);
+        assert_eq!(tree.sum_range(100..4000), Some(Counted((100..4000).sum())));
+        // 两条边界路径上各约log n个节点，每个节点最多两次加法，远小于范围内的元素个数
+        assert!(ADDS.load(AtomicOrdering::Relaxed) <= 4 * 13);
+        ADDS.store(0, AtomicOrdering::Relaxed);
+        assert!(tree.update(&2048, |value| *value = Counted(0)));
+        // 只重新计算查找路径上的节点
+        assert!(ADDS.load(AtomicOrdering::Relaxed) <= 2 * 13);
+        tree.insert(5000, Counted(1));
+        assert_eq!(
+            tree.sum_range(..),
+            Some(Counted((0..4096).sum::<u64>() - 2048 + 1))
+        );
+    }
+
+    #[test]
+    fn to_pretty_string() {
+        let mut tree = AVLTree::new();
+        for key in 1..=6 {
+            tree.insert(key, key * 10);
+        }
+        let expected = "\
+4: 40
+  L 2: 20
+    L 1: 10
+    R 3: 30
+  R 5: 50
+    R 6: 60
+";
+        assert_eq!(tree.to_pretty_string(), expected);
+        assert_eq!(
+            tree.to_string(),
+            "[K: 4, V: 40, L: [K: 2, V: 20, L: [K: 1, V: 10, L: Ø, R: Ø], \
+             R: [K: 3, V: 30, L: Ø, R: Ø]], R: [K: 5, V: 50, L: Ø, R: [K: 6, V: 60, L: Ø, R: Ø]]]"
+        );
+        assert_eq!(AVLTree::<i32, i32>::new().to_pretty_string(), "");
+
+        let large: AVLTree<i32, i32> = (0..100_000).map(|key| (key, key)).collect();
+        assert_eq!(large.to_pretty_string().lines().count(), 100_000);
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut tree = AVLTree::new();
+        for key in 0..100 {
+            let value = tree.try_insert(key, key.to_string()).unwrap();
+            value.push('!');
+        }
+        assert!(tree.is_avl_tree());
+        for key in (0..100).step_by(7) {
+            let mut err = match tree.try_insert(key, "new".to_string()) {
+                Err(TryInsertError::Occupied(err)) => err,
+                _ => panic!("key {} should be occupied", key),
+            };
+            assert_eq!(err.key, key);
+            assert_eq!(err.value, "new");
+            assert_eq!(err.entry.key(), &key);
+            err.entry.get_mut().push('?');
+            assert!(err.to_string().contains("already exists"));
+        }
+        for key in 0..100 {
+            let expected = if key % 7 == 0 {
+                format!("{}!?", key)
+            } else {
+                format!("{}!", key)
+            };
+            assert_eq!(tree.get(&key), Some(&expected));
+        }
+    }
+
+    #[test]
+    fn iter_from() {
+        let tree: AVLTree<i32, i32> = (0..10).map(|key| (key, -key)).collect();
+        let keys: Vec<i32> = tree.iter_from(&5).map(|(k, _)| *k).collect();
+        assert_eq!(keys, (5..10).collect::<Vec<_>>());
+        assert_eq!(tree.iter_from(&-3).count(), 10);
+        assert_eq!(tree.iter_from(&10).next(), None);
+
+        let mut tree = AVLTree::new();
+        for key in random_keys(500, 131) {
+            tree.insert(key % 1000, key);
+        }
+        for start in (-10..1010).step_by(7) {
+            assert!(tree.iter_from(&start).eq(tree.range(start..)));
+        }
+    }
+
+    #[test]
+    fn keys_in_range() {
+        let mut tree = AVLTree::new();
+        for key in random_keys(300, 137) {
+            tree.insert((key % 500).to_string(), key);
+        }
+        let ranges = [
+            (
+                Bound::Included("1".to_string()),
+                Bound::Excluded("3".to_string()),
+            ),
+            (Bound::Excluded("25".to_string()), Bound::Unbounded),
+            (Bound::Unbounded, Bound::Included("42".to_string())),
+        ];
+        for range in ranges {
+            let expected: Vec<String> = tree.range(range.clone()).map(|(k, _)| k.clone()).collect();
+            assert_eq!(tree.keys_in_range(range), expected);
+        }
+        assert!(tree.keys_in_range("a".to_string()..).is_empty());
+    }
+
+    #[test]
+    fn min_and_max_by_value() {
+        let mut tree = AVLTree::new();
+        assert_eq!(tree.min_by_value(), None);
+        for key in 0..100 {
+            tree.insert(key, (key * 37 + 11) % 101);
+        }
+        let expected_min = (0..100).min_by_key(|key| (key * 37 + 11) % 101).unwrap();
+        let expected_max = (0..100).max_by_key(|key| (key * 37 + 11) % 101).unwrap();
+        assert_ne!(expected_min, 0);
+        assert_ne!(expected_max, 99);
+        assert_eq!(tree.min_by_value(), tree.get_pair(&expected_min));
+        assert_eq!(tree.max_by_value(), tree.get_pair(&expected_max));
+
+        // 值相同时min_by_value取键最小的，max_by_value取键最大的
+        let min_value = *tree.get(&expected_min).unwrap();
+        let max_value = *tree.get(&expected_max).unwrap();
+        tree.insert(500, min_value);
+        tree.insert(600, max_value);
+        assert_eq!(tree.min_by_value(), Some((&expected_min, &min_value)));
+        assert_eq!(tree.max_by_value(), Some((&600, &max_value)));
+    }
+
+    #[test]
+    fn collapse_equal_values() {
+        let mut alternating: AVLTree<i32, bool> = (0..50).map(|key| (key, key % 2 == 0)).collect();
+        alternating.collapse_equal_values();
+        assert_eq!(alternating.len(), 50);
+
+        let mut runs: AVLTree<i32, i32> = (0..100).map(|key| (key, key / 10)).collect();
+        runs.collapse_equal_values();
+        assert!(runs.is_avl_tree());
+        assert_eq!(
+            runs.keys().copied().collect::<Vec<_>>(),
+            (0..100).step_by(10).collect::<Vec<_>>()
+        );
+
+        let mut mixed: AVLTree<i32, char> = "aabbbabccc"
+            .chars()
+            .enumerate()
+            .map(|(key, value)| (key as i32, value))
+            .collect();
+        mixed.collapse_equal_values();
+        let survivors: Vec<(i32, char)> = mixed.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(
+            survivors,
+            vec![(0, 'a'), (2, 'b'), (5, 'a'), (6, 'b'), (7, 'c')]
+        );
+    }
+
+    #[test]
+    fn keys_subset_and_superset() {
+        let tree_of =
+            |keys: &[i32]| -> AVLTree<i32, ()> { keys.iter().map(|&k| (k, ())).collect() };
+        let all: Vec<i32> = random_keys(300, 139);
+        let full = tree_of(&all);
+        let same = tree_of(&all);
+        assert!(full.keys_subset_of(&same) && full.keys_superset_of(&same));
+
+        let part = tree_of(&all[..150]);
+        assert!(part.keys_subset_of(&full));
+        assert!(!full.keys_subset_of(&part));
+        assert!(full.keys_superset_of(&part));
+        assert!(!part.keys_superset_of(&full));
+
+        let disjoint = tree_of(&[-1, -2, -3]);
+        assert!(!disjoint.keys_subset_of(&full));
+        assert!(!full.keys_superset_of(&disjoint));
+
+        let mut almost = tree_of(&all[..150]);
+        almost.insert(-5, ());
+        assert!(!almost.keys_subset_of(&full));
+
+        let empty = tree_of(&[]);
+        assert!(empty.keys_subset_of(&full));
+        assert!(full.keys_superset_of(&empty));
+        assert!(empty.keys_subset_of(&empty));
+    }
+
+    #[test]
+    fn range_filter() {
+        let mut tree = AVLTree::new();
+        for key in random_keys(500, 149) {
+            tree.insert(key % 1000, key);
+        }
+        let found: Vec<(&i32, &i32)> = tree
+            .range_filter(200..700, |value| value % 3 == 0)
+            .collect();
+        let expected: Vec<(&i32, &i32)> = tree
+            .iter()
+            .filter(|(k, v)| (200..700).contains(*k) && *v % 3 == 0)
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+        assert_eq!(tree.range_filter(.., |_| false).count(), 0);
+    }
+
+    #[test]
+    fn swap_values() {
+        let mut tree: AVLTree<i32, String> = (0..20).map(|key| (key, key.to_string())).collect();
+        assert!(tree.swap_values(&3, &17));
+        assert_eq!(tree.get(&3).map(String::as_str), Some("17"));
+        assert_eq!(tree.get(&17).map(String::as_str), Some("3"));
+
+        let before = tree.clone();
+        assert!(!tree.swap_values(&5, &5));
+        assert!(!tree.swap_values(&5, &50));
+        assert!(!tree.swap_values(&-1, &5));
+        assert_eq!(tree, before);
+    }
+
+    #[test]
+    fn swap_values_ancestor_keys() {
+        // 两个节点的树中1为根节点，是2的祖先，交换时两个值的可变借用同时存在
+        let mut tree = AVLTree::new();
+        tree.insert(1, String::from("root"));
+        tree.insert(2, String::from("leaf"));
+        assert!(tree.swap_values(&1, &2));
+        assert!(tree.swap_values(&2, &1));
+        assert!(tree.swap_values(&1, &2));
+        assert_eq!(tree.get(&1).map(String::as_str), Some("leaf"));
+        assert_eq!(tree.get(&2).map(String::as_str), Some("root"));
+        assert!(tree.is_avl_tree());
+    }
+
+    #[test]
+    fn with_key_bounds() {
+        let mut shard = AVLTree::with_key_bounds(100, 199);
+        for key in random_keys(500, 151) {
+            let key = key % 300;
+            let inside = (100..=199).contains(&key);
+            assert_eq!(shard.checked_insert(key, -key).is_ok(), inside);
+            shard.insert(key + 1, key);
+        }
+        assert!(shard.is_avl_tree());
+        assert!(shard.keys().all(|key| (100..=199).contains(key)));
+        assert_eq!(shard.min_pair().map(|(k, _)| *k), Some(100));
+        assert_eq!(shard.checked_insert(200, 0), Err((200, 0)));
+        assert!(shard.insert_traced(99, 0).is_empty());
+        assert!(!shard.contains(&99));
+
+        // 拆分得到的树沿用同一区间
+        let mut upper = shard.split_off(&150);
+        upper.insert(500, 0);
+        upper.insert(180, 0);
+        assert!(!upper.contains(&500));
+        assert_eq!(upper.get(&180), Some(&0));
+
+        let mut unbounded = AVLTree::new();
+        assert_eq!(unbounded.checked_insert(i32::MAX, 0), Ok(()));
+    }
+
+    #[test]
+    fn with_key_bounds_entry_rejects_outside_keys() {
+        let mut shard: AVLTree<i32, String> = AVLTree::with_key_bounds(0, 9);
+        shard.insert(5, "five".to_string());
+
+        assert!(matches!(shard.entry(10), Err(10)));
+        assert!(matches!(shard.entry(-1), Err(-1)));
+        shard
+            .entry(9)
+            .unwrap()
+            .or_insert_with(|| "nine".to_string());
+        assert_eq!(shard.get(&9).map(String::as_str), Some("nine"));
+
+        let mut calls = 0;
+        let rejected = shard.get_or_insert_with(20, || {
+            calls += 1;
+            String::new()
+        });
+        assert_eq!(rejected, Err(20));
+        assert_eq!(calls, 0);
+        shard.get_or_insert_with(0, String::new).unwrap().push('0');
+        assert_eq!(shard.get(&0).map(String::as_str), Some("0"));
+
+        match shard.try_insert(100, "hundred".to_string()) {
+            Err(err @ TryInsertError::OutOfBounds(..)) => {
+                assert!(err.to_string().contains("outside the tree's key bounds"));
+                if let TryInsertError::OutOfBounds(key, value) = err {
+                    assert_eq!((key, value.as_str()), (100, "hundred"));
+                }
+            }
+            _ => panic!("key 100 should be rejected"),
+        }
+        assert!(matches!(
+            shard.try_insert(5, "again".to_string()),
+            Err(TryInsertError::Occupied(_))
+        ));
+        assert_eq!(shard.try_insert(1, "one".to_string()).unwrap(), "one");
+
+        assert_eq!(shard.keys().copied().collect::<Vec<_>>(), vec![0, 1, 5, 9]);
+        assert!(shard.is_avl_tree());
+    }
+
+    #[test]
+    fn with_key_bounds_helpers_keep_values() {
+        // 新键在区间外时old的值通过Err退回，而不是被insert静默丢弃
+        let mut shard = AVLTree::with_key_bounds(0, 9);
+        shard.insert(1, 'a');
+        assert_eq!(shard.replace_key(&1, 50), Err('a'));
+        assert!(shard.is_empty());
+        shard.insert(1, 'a');
+        assert_eq!(shard.replace_key(&1, 1), Ok(()));
+        assert_eq!(shard.replace_key(&1, 9), Ok(()));
+        assert_eq!(shard.get(&9), Some(&'a'));
+
+        // append不检查区间，键区间重叠时逐个插入也不能丢弃other中的键
+        let mut shard: AVLTree<i32, i32> = AVLTree::with_key_bounds(0, 9);
+        shard.extend((0..10).map(|key| (key, key)));
+        let mut other: AVLTree<i32, i32> = (5..15).map(|key| (key, -key)).collect();
+        shard.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(shard.len(), 15);
+        assert_eq!(shard.get(&12), Some(&-12));
+        assert!(shard.is_avl_tree());
+
+        // 拆分后放回的边界键不受区间限制
+        shard.retain_range(..=12);
+        assert_eq!(shard.max_pair(), Some((&12, &-12)));
+        assert_eq!(shard.remove_range(..12), 12);
+        assert_eq!(shard.keys().copied().collect::<Vec<_>>(), vec![12]);
+    }
+
+    #[test]
+    fn select_range() {
+        let tree: AVLTree<i32, i32> = (0..100).map(|key| (key * 3, key)).collect();
+        assert!(tree.select_range(10, 15).eq(tree.iter().skip(10).take(5)));
+        for start in 0..=105 {
+            for end in [start, start + 1, start + 7, 100, 200] {
+                let expected: Vec<(&i32, &i32)> = tree
+                    .iter()
+                    .skip(start)
+                    .take(end.saturating_sub(start))
+                    .collect();
+                assert_eq!(tree.select_range(start, end).collect::<Vec<_>>(), expected);
+            }
+        }
+        assert_eq!(tree.select_range(20, 10).count(), 0);
+        assert_eq!(AVLTree::<i32, i32>::new().select_range(0, 5).count(), 0);
+    }
+
+    #[test]
+    fn clone_from_reuses_tree() {
+        let source: AVLTree<i32, String> = (0..50).map(|key| (key, key.to_string())).collect();
+        // 目标树分别比源树大、小以及为空
+        for len in [0, 10, 50, 200] {
+            let mut target: AVLTree<i32, String> =
+                (0..len).map(|key| (key * 2, String::from("old"))).collect();
+            target.clone_from(&source);
+            assert_eq!(target, source);
+            assert!(target.validate().is_ok());
+            assert_eq!(target.len(), source.len());
+            assert_eq!(target.first_key_value(), source.first_key_value());
+            assert_eq!(target.last_key_value(), source.last_key_value());
+        }
+        let mut target = source.clone();
+        target.clone_from(&AVLTree::new());
+        assert!(target.is_empty());
+    }
+
+    #[test]
+    fn peek_min_and_max() {
+        let mut tree: AVLTree<i32, i32> = (1..=10).map(|key| (key, key * 10)).collect();
+        assert_eq!(tree.peek_min(), tree.min_pair());
+        assert_eq!(tree.peek_max(), tree.max_pair());
+        if let Some((key, value)) = tree.peek_min_mut() {
+            assert_eq!(*key, 1);
+            *value += 5;
+        }
+        assert_eq!(tree.peek_min(), Some((&1, &15)));
+        if let Some((_, value)) = tree.peek_max_mut() {
+            *value = 0;
+        }
+        assert_eq!(tree.peek_max(), Some((&10, &0)));
+        assert_eq!(tree.len(), 10);
+        assert!(tree.validate().is_ok());
+        let mut empty: AVLTree<i32, i32> = AVLTree::new();
+        assert_eq!(empty.peek_min(), None);
+        assert_eq!(empty.peek_max_mut(), None);
+    }
+
+    #[test]
+    fn same_shape_depends_on_insert_order() {
+        let ascending: AVLTree<i32, i32> = (1..=6).map(|key| (key, key)).collect();
+        let descending: AVLTree<i32, i32> = (1..=6).rev().map(|key| (key, key)).collect();
+        assert_eq!(ascending, descending);
+        assert!(!ascending.same_shape(&descending));
+        assert!(ascending.same_shape(&ascending.clone()));
+        // 值不参与比较
+        let strings = ascending.map_values(|value| value.to_string());
+        let ascending: AVLTree<i32, i32> = (1..=6).map(|key| (key, key)).collect();
+        assert!(ascending.same_shape(&strings));
+        assert!(AVLTree::<i32, i32>::new().same_shape(&AVLTree::<i32, ()>::new()));
+        assert!(!ascending.same_shape(&AVLTree::<i32, i32>::new()));
+    }
+
+    #[test]
+    fn range_rev() {
+        let tree: AVLTree<i32, i32> = (1..=9).map(|key| (key, key)).collect();
+        let keys: Vec<i32> = tree.range_rev(2..=5).map(|(key, _)| *key).collect();
+        assert_eq!(keys, vec![5, 4, 3, 2]);
+        for (lower, upper) in [(0, 10), (3, 3), (4, 7), (8, 20)] {
+            let expected: Vec<_> = tree.range(lower..upper).rev().collect();
+            assert_eq!(tree.range_rev(lower..upper).collect::<Vec<_>>(), expected);
+        }
+        assert_eq!(tree.range_rev(5..).next(), Some((&9, &9)));
+        assert_eq!(tree.range_rev(..=0).count(), 0);
+        let ascending: Vec<i32> = tree.range_rev(3..6).rev().map(|(key, _)| *key).collect();
+        assert_eq!(ascending, vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn apply_range() {
+        let mut tree: AVLTree<i32, i32> = (0..10).map(|key| (key, key * 10)).collect();
+        let mut visited = Vec::new();
+        tree.apply_range(3..7, |key, value| {
+            visited.push(*key);
+            *value += 1;
+        });
+        assert_eq!(visited, vec![3, 4, 5, 6]);
+        for (key, value) in tree.iter() {
+            let expected = if (3..7).contains(key) {
+                key * 10 + 1
+            } else {
+                key * 10
+            };
+            assert_eq!(*value, expected);
+        }
+        tree.apply_range(20.., |_, _| panic!("out of range"));
+        assert!(tree.validate().is_ok());
+    }
+
+    #[test]
+    fn get_pair_mut() {
+        // 比较时只看名字，附带的编号不参与比较
+        #[derive(Debug, Clone)]
+        struct Tagged(&'static str, u32);
+        impl PartialEq for Tagged {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+        impl Eq for Tagged {}
+        impl PartialOrd for Tagged {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Tagged {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0.cmp(other.0)
+            }
+        }
+
+        let mut tree = AVLTree::new();
+        tree.insert(Tagged("a", 7), 0);
+        tree.insert(Tagged("b", 9), 0);
+        let (key, value) = tree.get_pair_mut(&Tagged("a", 0)).unwrap();
+        assert_eq!(key.1, 7);
+        *value = key.1 * 2;
+        assert_eq!(tree.get(&Tagged("a", 0)), Some(&14));
+        assert_eq!(tree.get(&Tagged("b", 0)), Some(&0));
+        assert!(tree.get_pair_mut(&Tagged("c", 0)).is_none());
+    }
+
+    #[test]
+    fn levelorder_with_depth() {
+        let mut tree = AVLTree::new();
+        for key in [4, 2, 6, 1, 3, 5, 7] {
+            tree.insert(key, key * 10);
+        }
+        let levels = tree.levelorder_with_depth();
+        let depths: Vec<usize> = levels.iter().map(|(depth, _, _)| *depth).collect();
+        assert_eq!(depths, vec![0, 1, 1, 2, 2, 2, 2]);
+        let pairs: Vec<(&i32, &i32)> = levels.iter().map(|&(_, key, value)| (key, value)).collect();
+        assert_eq!(pairs, tree.levelorder_iter().collect::<Vec<_>>());
+        assert!(AVLTree::<i32, i32>::new()
+            .levelorder_with_depth()
+            .is_empty());
+    }
+
+    #[test]
+    fn compact_round_trip() {
+        let tree: AVLTree<i32, i32> = (0..1000).map(|key| (key * 2, key)).collect();
+        let expected = tree.clone();
+        let compact = tree.compact();
+        assert_eq!(compact.len(), 1000);
+        assert!(compact.iter().eq(expected.iter()));
+        for key in 0..2000 {
+            assert_eq!(compact.get(&key), expected.get(&key));
+        }
+        let tree = compact.into_tree();
+        assert_eq!(tree, expected);
+        assert!(tree.validate().is_ok());
+
+        // 比较规则和键的区间在转换中保留
+        let mut descending = AVLTree::new_by(|a: &i32, b: &i32| b.cmp(a));
+        descending.extend((0..10).map(|key| (key, ())));
+        let compact = descending.compact();
+        assert_eq!(compact.iter().next(), Some((&9, &())));
+        assert!(compact.contains_key(&3));
+        assert_eq!(compact.into_tree().keys().next(), Some(&9));
+        let mut shard: AVLTree<i32, i32> = AVLTree::with_key_bounds(0, 9);
+        shard.insert(1, 1);
+        let mut shard = shard.compact().into_tree();
+        assert!(shard.checked_insert(10, 0).is_err());
+        assert!(AVLTree::<i32, i32>::new().compact().is_empty());
+    }
+
+    #[test]
+    fn btreemap_round_trip() {
+        use std::collections::BTreeMap;
+
+        let map: BTreeMap<i32, String> = (0..500).map(|key| (key * 3, key.to_string())).collect();
+        let tree = AVLTree::from(map.clone());
+        assert!(tree.validate().is_ok());
+        assert_eq!(tree.len(), map.len());
+        assert!(tree.iter().eq(map.iter()));
+        assert_eq!(tree.into_btreemap(), map);
+
+        let tree: AVLTree<i32, i32> = (0..100).rev().map(|key| (key, key * key)).collect();
+        let expected = tree.clone();
+        let map = tree.into_btreemap();
+        assert!(map.iter().eq(expected.iter()));
+        assert_eq!(AVLTree::from(map), expected);
+        assert!(AVLTree::from(BTreeMap::<i32, i32>::new()).is_empty());
+    }
 }